@@ -1,184 +1,154 @@
+#[cfg(target_arch = "wasm32")]
 cargo_component_bindings::generate!();
+#[cfg(target_arch = "wasm32")]
 use crate::bindings::exports::golem::template::api::*;
 
-use dialog_engine::{HasDialogMessage, validate_rating};
-use frankenstein::{Update, UpdateContent};
-use once_cell::sync::Lazy;
-use serde::Serialize;
-use telegram_api::*;
+// Natively the dialog is called in-process, with the records of the WIT interface defined in `native`
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
 
-use std::env;
+use dialog_engine::{DialogSettings, Field, Form, FormState, Language, Value, Values, Widget, parse_date, t};
+use frankenstein::Update;
+#[cfg(target_arch = "wasm32")]
+use once_cell::sync::Lazy;
 
+#[cfg(target_arch = "wasm32")]
 struct Component;
 
-/// This is one of any number of data types that our application
-/// uses. Golem will take care to persist all application state,
-/// whether that state is local to a function being executed or
-/// global across the entire program.
-struct State {
-    dialog_state: DialogState,
-}
-
 /// This holds the state of our application.
 /// It is a global variable, which Rust doesn't like, so
 /// we use `with_state` to access or update the global variable, so we
 /// can avoid `unsafe` noise.
-static mut STATE: State = State {
-    dialog_state: DialogState::Started
-};
+#[cfg(target_arch = "wasm32")]
+static mut STATE: Lazy<FormState> = Lazy::new(|| FormState::new(DialogSettings::from_env()));
+
+#[cfg(target_arch = "wasm32")]
+fn with_state<T>(f: impl FnOnce(&mut FormState) -> T) -> T {
+    unsafe { f(&mut *STATE) }
+}
 
-fn with_state<T>(f: impl FnOnce(&mut State) -> T) -> T {
-    unsafe { f(&mut STATE) }
+fn shelf_code(status: ReadingStatus) -> &'static str {
+    match status {
+        ReadingStatus::WantToRead => "want-to-read",
+        ReadingStatus::Reading => "reading",
+        ReadingStatus::Finished => "finished",
+        ReadingStatus::Abandoned => "abandoned",
+    }
 }
 
-static TELEGRAM_TOKEN: Lazy<String> = Lazy::new(|| {
-    env::var("TELEGRAM_TOKEN").unwrap()
-});
-
-#[derive(Debug, Clone, Serialize)]
-enum DialogState {
-    Started,
-    EnterTitle,
-    EnterAuthor(String),
-    // (Title)
-    EnterRating(String, String),
-    // (Title, Author)
-    Completed(String, String, u32), // (Title, Author, Rating)
+fn shelf_status(code: &str) -> ReadingStatus {
+    match code {
+        "want-to-read" => ReadingStatus::WantToRead,
+        "reading" => ReadingStatus::Reading,
+        "abandoned" => ReadingStatus::Abandoned,
+        _ => ReadingStatus::Finished,
+    }
 }
 
-#[derive(Debug)]
-enum Event {
-    Start,
-    ProvideTitle(String),
-    ProvideAuthor(String),
-    ProvideRating(u32),
+fn shelf_name(status: ReadingStatus, language: Language) -> String {
+    let key = match status {
+        ReadingStatus::WantToRead => "shelf.want",
+        ReadingStatus::Reading => "shelf.reading",
+        ReadingStatus::Finished => "shelf.finished",
+        ReadingStatus::Abandoned => "shelf.abandoned",
+    };
+    t!(language, key)
 }
 
-impl DialogState {
-    fn next(self, event: Event) -> Self {
-        use DialogState::*;
-        use Event::*;
-
-        match (self, event) {
-            (Started, Start) => EnterTitle,
-            (EnterTitle, ProvideTitle(title)) => EnterAuthor(title),
-            (EnterAuthor(title), ProvideAuthor(author)) => EnterRating(title, author),
-            (EnterRating(title, author), ProvideRating(rating)) => Completed(title, author, rating),
-            (state, event) => {
-                println!("Unexpected state transition: {:?} -> {:?}", &state, &event);
-                state
-            }
-        }
-    }
+fn parse_shelf(text: &String, settings: &DialogSettings) -> Result<Value, String> {
+    let code = match text.trim().to_lowercase().as_str() {
+        "1" | "want" | "want to read" | "want-to-read" | "хочу прочитать" => "want-to-read",
+        "2" | "reading" | "currently reading" | "читаю" => "reading",
+        "3" | "finished" | "read" | "прочитано" => "finished",
+        "4" | "abandoned" | "брошено" => "abandoned",
+        _ => return Err(t!(settings.language, "book.status_error")),
+    };
+    Ok(Value::Text(code.to_string()))
 }
 
-impl HasDialogMessage for DialogState {
-    fn message(&self) -> Option<String> {
-        use DialogState::*;
-
-        match self {
-            Started => None,
-            EnterTitle => Some("Enter title".to_string()),
-            EnterAuthor(_) => Some("Enter author".to_string()),
-            EnterRating(_, _) => Some("Enter rating".to_string()),
-            Completed(title, author, rating) => Some(format!("Added book {} by {} with rating {}", title, author, rating)),
-        }
+// Books that aren't finished have no rating and no date read yet
+fn is_finished(values: &Values) -> bool {
+    values.text("status") == "finished"
+}
+
+/// The confirmation sent when the dialog completes, `action` is `added` or `updated`.
+fn describe(action: &str, book: &Book, settings: &DialogSettings) -> String {
+    let language = settings.language;
+    let (title, author) = (&book.title, &book.author);
+    match (book.rating, &book.date) {
+        (Some(rating), Some(date)) => t!(language, &format!("book.{}.rated_on", action), title = title, author = author, rating = settings.rating_scale.format(rating), date = date),
+        (Some(rating), None) => t!(language, &format!("book.{}.rated", action), title = title, author = author, rating = settings.rating_scale.format(rating)),
+        _ => t!(language, &format!("book.{}.shelved", action), title = title, author = author, shelf = shelf_name(book.status, language)),
     }
 }
 
-const EMPTY_RESULT: Result<DialogResult, String> = Ok(DialogResult { book: None });
+fn form(settings: &DialogSettings) -> Form<Book> {
+    let language = settings.language;
+    Form {
+        fields: vec![
+            Field::text("title", &t!(language, "prompt.title")),
+            Field::text("author", &t!(language, "prompt.author")),
+            Field::new("status", &t!(language, "book.status"), parse_shelf),
+            Field::rating("rating", settings).when(is_finished),
+            Field::new("date", &t!(language, "book.date", hint = t!(language, "date.hint")), parse_date).widget(Widget::Calendar).when(is_finished).optional(),
+        ],
+        build: |values| Book {
+            title: values.text("title"),
+            author: values.text("author"),
+            status: shelf_status(&values.text("status")),
+            rating: values.optional_number("rating"),
+            date: values.optional_text("date"),
+        },
+        added: |book, settings| describe("added", book, settings),
+        updated: |book, settings| describe("updated", book, settings),
+    }
+}
 
-fn advance_dialog_state(state: &mut State, event: Event) -> DialogState {
-    let new_dialog_state = state.dialog_state.clone().next(event);
-    state.dialog_state = new_dialog_state.clone();
-    new_dialog_state
+fn values(book: Book) -> Values {
+    Values::new()
+        .with("title", book.title)
+        .with("author", book.author)
+        .with("status", shelf_code(book.status).to_string())
+        .with_optional("rating", book.rating)
+        .with_optional("date", book.date)
 }
 
-fn advance_dialog_and_send_message(api: &Api, chat_id: i64, state: &mut State, event: Event) {
-    let new_state = advance_dialog_state(state, event);
-    send_dialog_message(api, chat_id, &new_state);
+/// Handles an update sent to the dialog, the book is returned once the last answer is given.
+pub fn step(state: &mut FormState, update: &str) -> Result<DialogResult, String> {
+    let update: Update = serde_json::from_str(update)
+        .map_err(|err| format!("Update JSON deserialization failed: {}", err))?;
+    let api = state.settings.api()?;
+    let form = form(&state.settings);
+    let book = form.handle_update(state, &api, &update)?;
+    Ok(DialogResult { book, confirmation: form.confirmation(state) })
 }
 
-fn handle_update(state: &mut State, api: &Api, update: &Update) -> Result<DialogResult, String> {
-    let dialog_state = &state.dialog_state;
-    println!("Dialog state: {:?}", dialog_state);
-    match dialog_state {
-        DialogState::Started => {
-            if let UpdateContent::Message(ref message) = update.content {
-                advance_dialog_and_send_message(api, message.chat.id, state, Event::Start);
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterTitle => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    advance_dialog_and_send_message(api, message.chat.id, state, Event::ProvideTitle(text.clone()));
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterAuthor(_) => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    advance_dialog_and_send_message(api, message.chat.id, state, Event::ProvideAuthor(text.clone()));
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterRating(_, _) => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    // rating is a number between 1 and 5, validate and send error message if invalid
-                    let rating_result = validate_rating(text);
-                    match rating_result {
-                        Ok(rating) => {
-                            advance_dialog_state(state, Event::ProvideRating(rating));
-                        }
-                        Err(err) => {
-                            send_message(api, message.chat.id, err);
-                            return EMPTY_RESULT;
-                        }
-                    }
-                }
-                let current_state = &state.dialog_state;
-                if let DialogState::Completed(ref title, ref author, ref rating) = current_state {
-                    send_dialog_message(api, message.chat.id, &current_state);
-                    return Ok(DialogResult {
-                        book: Some(Book {
-                            title: title.clone(),
-                            author: author.clone(),
-                            rating: *rating,
-                        })
-                    });
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::Completed(ref title, ref author, ref rating) => {
-            Ok(DialogResult {
-                book: Some(Book {
-                    title: title.clone(),
-                    author: author.clone(),
-                    rating: *rating,
-                })
-            })
-        }
-    }
+/// Starts the dialog prefilled with the answers of an existing book.
+pub fn edit(state: &mut FormState, chat_id: i64, book: Book) -> Result<DialogResult, String> {
+    let api = state.settings.api()?;
+    form(&state.settings).edit(state, &api, chat_id, values(book));
+    Ok(DialogResult { book: None, confirmation: None })
 }
 
-fn send_dialog_message(api: &Api, chat_id: i64, dialog_state: &DialogState) {
-    if let Some(message) = dialog_state.message() {
-        send_message(api, chat_id, &message);
-    }
+/// The state of the form as JSON, for inspecting a running dialog.
+pub fn state(state: &FormState) -> Result<String, String> {
+    serde_json::to_string(state)
+        .map_err(|err| format!("JSON serialization failed: {}", err))
 }
 
+#[cfg(target_arch = "wasm32")]
 impl Guest for Component {
     fn step(update: String) -> Result<DialogResult, String> {
-        with_state(|state| {
-            let update: Update = serde_json::from_str(&update)
-                .map_err(|err| format!("Update JSON deserialization failed: {}", err))?;
-            let api = Api::new(TELEGRAM_TOKEN.as_str());
-            handle_update(state, &api, &update)
-        })
+        with_state(|state| step(state, &update))
+    }
+
+    fn edit(chat_id: i64, book: Book) -> Result<DialogResult, String> {
+        with_state(|state| edit(state, chat_id, book))
+    }
+
+    fn state() -> Result<String, String> {
+        with_state(|state| crate::state(state))
     }
 }
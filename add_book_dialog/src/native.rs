@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+// The records of `wit/add-book-dialog.wit`, serialized like Golem does in its JSON API
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DialogResult {
+    pub book: Option<Book>,
+    // Sent by the bot once the book is saved
+    pub confirmation: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReadingStatus {
+    WantToRead,
+    Reading,
+    Finished,
+    Abandoned,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Book {
+    pub title: String,
+    pub author: String,
+    pub status: ReadingStatus,
+    // Score out of 100, independent of the rating scale of the user
+    pub rating: Option<u32>,
+    pub date: Option<String>,
+}
@@ -1,208 +1,122 @@
+#[cfg(target_arch = "wasm32")]
 cargo_component_bindings::generate!();
+#[cfg(target_arch = "wasm32")]
 use crate::bindings::exports::golem::template::api::*;
 
-use dialog_engine::{HasDialogMessage, validate_rating};
-use frankenstein::{Update, UpdateContent};
-use once_cell::sync::Lazy;
-use serde::Serialize;
-use telegram_api::*;
+// Natively the dialog is called in-process, with the records of the WIT interface defined in `native`
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
 
-use std::env;
+use dialog_engine::{DialogSettings, Field, Form, FormState, Values, Widget, parse_date, parse_year, t};
+use frankenstein::Update;
+#[cfg(target_arch = "wasm32")]
+use once_cell::sync::Lazy;
 
+#[cfg(target_arch = "wasm32")]
 struct Component;
 
-/// This is one of any number of data types that our application
-/// uses. Golem will take care to persist all application state,
-/// whether that state is local to a function being executed or
-/// global across the entire program.
-struct State {
-    dialog_state: DialogState,
-}
-
 /// This holds the state of our application.
 /// It is a global variable, which Rust doesn't like, so
 /// we use `with_state` to access or update the global variable, so we
 /// can avoid `unsafe` noise.
-static mut STATE: State = State {
-    dialog_state: DialogState::Started
-};
-
-fn with_state<T>(f: impl FnOnce(&mut State) -> T) -> T {
-    unsafe { f(&mut STATE) }
-}
-
-static TELEGRAM_TOKEN: Lazy<String> = Lazy::new(|| {
-    env::var("TELEGRAM_TOKEN").unwrap()
-});
-
-#[derive(Debug, Clone, Serialize)]
-enum DialogState {
-    Started,
-    EnterTitle,
-    // (Title)
-    EnterYear(String),
-    // (Title, Year)
-    EnterRating(String, u32),
-    // (Title, Year, Rating)
-    Completed(String, u32, u32),
-}
+#[cfg(target_arch = "wasm32")]
+static mut STATE: Lazy<FormState> = Lazy::new(|| FormState::new(DialogSettings::from_env()));
 
-#[derive(Debug)]
-enum Event {
-    Start,
-    ProvideTitle(String),
-    ProvideYear(u32),
-    ProvideRating(u32),
+#[cfg(target_arch = "wasm32")]
+fn with_state<T>(f: impl FnOnce(&mut FormState) -> T) -> T {
+    unsafe { f(&mut *STATE) }
 }
 
-impl DialogState {
-    fn next(self, event: Event) -> Self {
-        use DialogState::*;
-        use Event::*;
-
-        match (self, event) {
-            (Started, Start) => EnterTitle,
-            (EnterTitle, ProvideTitle(title)) => EnterYear(title),
-            (EnterYear(title), ProvideYear(year)) => EnterRating(title, year),
-            (EnterRating(title, year), ProvideRating(rating)) => Completed(title, year, rating),
-            (state, event) => {
-                println!("Unexpected state transition: {:?} -> {:?}", &state, &event);
-                state
-            }
-        }
+fn form(settings: &DialogSettings) -> Form<Movie> {
+    let language = settings.language;
+    Form {
+        fields: vec![
+            Field::text("title", &t!(language, "prompt.title")),
+            Field::new("year", &t!(language, "prompt.year"), parse_year).widget(Widget::Year).optional(),
+            Field::rating("rating", settings),
+            Field::new("date", &t!(language, "movie.date", hint = t!(language, "date.hint")), parse_date).widget(Widget::Calendar).optional(),
+        ],
+        build: |values| Movie {
+            title: values.text("title"),
+            year: values.optional_number("year"),
+            rating: values.number("rating"),
+            date: values.optional_text("date"),
+        },
+        added: |movie, settings| t!(settings.language, "movie.added", movie = describe(movie, settings)),
+        updated: |movie, settings| t!(settings.language, "movie.updated", movie = describe(movie, settings)),
     }
 }
 
-impl HasDialogMessage for DialogState {
-    fn message(&self) -> Option<String> {
-        use DialogState::*;
-
-        match self {
-            Started => None,
-            EnterTitle => Some("Enter title".to_string()),
-            EnterYear(_) => Some("Enter year".to_string()),
-            EnterRating(_, _) => Some("Enter rating".to_string()),
-            Completed(title, year, rating) => Some(format!("Added movie {} ({}) with rating {}", title, year, rating)),
-        }
+fn describe(movie: &Movie, settings: &DialogSettings) -> String {
+    let language = settings.language;
+    let mut text = movie.title.clone();
+    if let Some(year) = movie.year {
+        text.push_str(&format!(" ({})", year));
     }
+    text.push_str(&t!(language, "movie.with_rating", rating = settings.rating_scale.format(movie.rating)));
+    if let Some(ref date) = movie.date {
+        text.push_str(&t!(language, "movie.watched_on", date = date));
+    }
+    text
 }
 
-const EMPTY_RESULT: Result<DialogResult, String> = Ok(DialogResult { movie: None });
+fn values(movie: Movie) -> Values {
+    Values::new()
+        .with("title", movie.title)
+        .with_optional("year", movie.year)
+        .with("rating", movie.rating)
+        .with_optional("date", movie.date)
+}
 
-fn advance_dialog_state(state: &mut State, event: Event) -> DialogState {
-    let new_dialog_state = state.dialog_state.clone().next(event);
-    state.dialog_state = new_dialog_state.clone();
-    new_dialog_state
+/// Handles an update sent to the dialog, the movie is returned once the last answer is given.
+pub fn step(state: &mut FormState, update: &str) -> Result<DialogResult, String> {
+    let update: Update = serde_json::from_str(update)
+        .map_err(|err| format!("Update JSON deserialization failed: {}", err))?;
+    let api = state.settings.api()?;
+    let form = form(&state.settings);
+    let movie = form.handle_update(state, &api, &update)?;
+    Ok(DialogResult { movie, confirmation: form.confirmation(state) })
 }
 
-fn advance_dialog_and_send_message(api: &Api, chat_id: i64, state: &mut State, event: Event) {
-    let new_state = advance_dialog_state(state, event);
-    send_dialog_message(api, chat_id, &new_state);
+/// Starts the dialog prefilled with the answers of an existing movie.
+pub fn edit(state: &mut FormState, chat_id: i64, movie: Movie) -> Result<DialogResult, String> {
+    let api = state.settings.api()?;
+    form(&state.settings).edit(state, &api, chat_id, values(movie));
+    Ok(DialogResult { movie: None, confirmation: None })
 }
 
-fn handle_update(state: &mut State, api: &Api, update: &Update) -> Result<DialogResult, String> {
-    let dialog_state = &state.dialog_state;
-    println!("Dialog state: {:?}", dialog_state);
-    match dialog_state {
-        DialogState::Started => {
-            if let UpdateContent::Message(ref message) = update.content {
-                advance_dialog_and_send_message(api, message.chat.id,state, Event::Start);
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterTitle => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    advance_dialog_and_send_message(api, message.chat.id, state, Event::ProvideTitle(text.clone()));
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterYear(_) => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    // year is a number between 1900 and 2100, validate and send error message if invalid
-                    let year_result = text.parse::<u32>();
-                    match year_result {
-                        Ok(year) => {
-                            if year >= 1900 && year <= 2100 {
-                                advance_dialog_state(state, Event::ProvideYear(year));
-                            } else {
-                                send_message(api, message.chat.id, "Year must be between 1900 and 2100");
-                                return EMPTY_RESULT;
-                            }
-                        }
-                        Err(_) => {
-                            send_message(api, message.chat.id, "Year must be a number");
-                            return EMPTY_RESULT;
-                        }
-                    }
-                    send_dialog_message(api, message.chat.id, &state.dialog_state);
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterRating(_, _) => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    // rating is a number between 1 and 5, validate and send error message if invalid
-                    let rating_result = validate_rating(text);
-                    match rating_result {
-                        Ok(rating) => {
-                            advance_dialog_state(state, Event::ProvideRating(rating));
-                        }
-                        Err(err) => {
-                            send_message(api, message.chat.id, err);
-                            return EMPTY_RESULT;
-                        }
-                    }
-                }
-                let current_state = &state.dialog_state;
-                if let DialogState::Completed(ref title, ref year, ref rating) = current_state {
-                    send_dialog_message(api, message.chat.id, &current_state);
-                    return Ok(DialogResult {
-                        movie: Some(Movie {
-                            title: title.clone(),
-                            year: year.clone(),
-                            rating: *rating,
-                        })
-                    });
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::Completed(ref title, ref year, ref rating) => {
-            Ok(DialogResult {
-                movie: Some(Movie {
-                    title: title.clone(),
-                    year: year.clone(),
-                    rating: *rating,
-                })
-            })
-        }
-    }
+/// Starts the dialog at the rating step for a title from the watchlist.
+pub fn watched(state: &mut FormState, chat_id: i64, title: String, year: Option<u32>) -> Result<DialogResult, String> {
+    let api = state.settings.api()?;
+    // The year is asked for if the watchlist doesn't know it
+    let known = Values::new().with("title", title).with_optional("year", year);
+    form(&state.settings).prefill(state, &api, chat_id, known);
+    Ok(DialogResult { movie: None, confirmation: None })
 }
 
-fn send_dialog_message(api: &Api, chat_id: i64, dialog_state: &DialogState) {
-    if let Some(message) = dialog_state.message() {
-        send_message(api, chat_id, &message);
-    }
+/// The state of the form as JSON, for inspecting a running dialog.
+pub fn state(state: &FormState) -> Result<String, String> {
+    serde_json::to_string(state)
+        .map_err(|err| format!("JSON serialization failed: {}", err))
 }
 
+#[cfg(target_arch = "wasm32")]
 impl Guest for Component {
     fn step(update: String) -> Result<DialogResult, String> {
-        with_state(|state| {
-            let update: Update = serde_json::from_str(&update)
-                .map_err(|err| format!("Update JSON deserialization failed: {}", err))?;
-            let api = Api::new(TELEGRAM_TOKEN.as_str());
-            handle_update(state, &api, &update)
-        })
+        with_state(|state| step(state, &update))
+    }
+
+    fn edit(chat_id: i64, movie: Movie) -> Result<DialogResult, String> {
+        with_state(|state| edit(state, chat_id, movie))
+    }
+
+    fn watched(chat_id: i64, title: String, year: Option<u32>) -> Result<DialogResult, String> {
+        with_state(|state| watched(state, chat_id, title, year))
     }
 
     fn state() -> Result<String, String> {
-        with_state(|state| {
-            serde_json::to_string(&state.dialog_state)
-                .map_err(|err| format!("JSON serialization failed: {}", err))
-        })
+        with_state(|state| crate::state(state))
     }
 }
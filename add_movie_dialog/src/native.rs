@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+// The records of `wit/add-movie-dialog.wit`, serialized like Golem does in its JSON API
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DialogResult {
+    pub movie: Option<Movie>,
+    // Sent by the bot once the movie is saved
+    pub confirmation: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Movie {
+    pub title: String,
+    pub year: Option<u32>,
+    // Score out of 100, independent of the rating scale of the user
+    pub rating: u32,
+    pub date: Option<String>,
+}
@@ -1,174 +1,103 @@
+#[cfg(target_arch = "wasm32")]
 cargo_component_bindings::generate!();
+#[cfg(target_arch = "wasm32")]
 use crate::bindings::exports::golem::template::api::*;
 
-use dialog_engine::HasDialogMessage;
-use frankenstein::{Update, UpdateContent};
-use once_cell::sync::Lazy;
-use serde::Serialize;
-use telegram_api::*;
+// Natively the dialog is called in-process, with the records of the WIT interface defined in `native`
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
 
-use std::env;
+use dialog_engine::{DialogSettings, Field, Form, FormState, Language, Values, t, today};
+use frankenstein::Update;
+#[cfg(target_arch = "wasm32")]
+use once_cell::sync::Lazy;
 
+#[cfg(target_arch = "wasm32")]
 struct Component;
 
-/// This is one of any number of data types that our application
-/// uses. Golem will take care to persist all application state,
-/// whether that state is local to a function being executed or
-/// global across the entire program.
-struct State {
-    dialog_state: DialogState,
-}
-
 /// This holds the state of our application.
 /// It is a global variable, which Rust doesn't like, so
 /// we use `with_state` to access or update the global variable, so we
 /// can avoid `unsafe` noise.
-static mut STATE: State = State {
-    dialog_state: DialogState::Started
-};
-
-fn with_state<T>(f: impl FnOnce(&mut State) -> T) -> T {
-    unsafe { f(&mut STATE) }
-}
-
-static TELEGRAM_TOKEN: Lazy<String> = Lazy::new(|| {
-    env::var("TELEGRAM_TOKEN").unwrap()
-});
-
-#[derive(Debug, Clone, Serialize)]
-enum DialogState {
-    Started,
-    EnterText,
-    // (Text)
-    EnterTitle(String),
-    // (Text, Title)
-    EnterAuthor(String, String),
-    // (Text, Title, Author)
-    Completed(String, String, String),
-}
+#[cfg(target_arch = "wasm32")]
+static mut STATE: Lazy<FormState> = Lazy::new(|| FormState::new(DialogSettings::from_env()));
 
-#[derive(Debug)]
-enum Event {
-    Start,
-    ProvideText(String),
-    ProvideTitle(String),
-    ProvideAuthor(String),
+#[cfg(target_arch = "wasm32")]
+fn with_state<T>(f: impl FnOnce(&mut FormState) -> T) -> T {
+    unsafe { f(&mut *STATE) }
 }
 
-impl DialogState {
-    fn next(self, event: Event) -> Self {
-        use DialogState::*;
-        use Event::*;
-
-        match (self, event) {
-            (Started, Start) => EnterText,
-            (EnterText, ProvideText(text)) => EnterTitle(text),
-            (EnterTitle(text), ProvideTitle(title)) => EnterAuthor(text, title),
-            (EnterAuthor(text, title), ProvideAuthor(author)) => Completed(text, title, author),
-            (state, event) => {
-                println!("Unexpected state transition: {:?} -> {:?}", &state, &event);
-                state
-            }
-        }
+fn form(settings: &DialogSettings) -> Form<Quote> {
+    let language = settings.language;
+    Form {
+        fields: vec![
+            Field::text("text", &t!(language, "quote.text")),
+            Field::text("title", &t!(language, "prompt.title")),
+            Field::text("author", &t!(language, "prompt.author")).optional(),
+        ],
+        build: |values| Quote {
+            text: values.text("text"),
+            title: values.text("title"),
+            author: values.optional_text("author"),
+            // The date isn't asked for, an edited quote keeps the date it was saved on
+            date: values.optional_text("date").or(Some(today())),
+        },
+        added: |quote, settings| t!(settings.language, "quote.added", quote = describe(quote, settings.language)),
+        updated: |quote, settings| t!(settings.language, "quote.updated", quote = describe(quote, settings.language)),
     }
 }
 
-impl HasDialogMessage for DialogState {
-    fn message(&self) -> Option<String> {
-        use DialogState::*;
-
-        match self {
-            Started => None,
-            EnterText => Some("Enter text".to_string()),
-            EnterTitle(_) => Some("Enter title".to_string()),
-            EnterAuthor(_, _) => Some("Enter author".to_string()),
-            Completed(text, title, author) => Some(format!("Added quote: \"{}\" from {} by {}", text, title, author)),
-        }
+fn describe(quote: &Quote, language: Language) -> String {
+    match quote.author {
+        Some(ref author) => t!(language, "quote.source_by", text = quote.text, title = quote.title, author = author),
+        None => t!(language, "quote.source", text = quote.text, title = quote.title),
     }
 }
 
-const EMPTY_RESULT: Result<DialogResult, String> = Ok(DialogResult { quote: None });
-
-fn advance_dialog_state(state: &mut State, event: Event) -> DialogState {
-    let new_dialog_state = state.dialog_state.clone().next(event);
-    state.dialog_state = new_dialog_state.clone();
-    new_dialog_state
+fn values(quote: Quote) -> Values {
+    Values::new()
+        .with("text", quote.text)
+        .with("title", quote.title)
+        .with_optional("author", quote.author)
+        .with_optional("date", quote.date)
 }
 
-fn advance_dialog_and_send_message(api: &Api, chat_id: i64, state: &mut State, event: Event) {
-    let new_state = advance_dialog_state(state, event);
-    send_dialog_message(api, chat_id, &new_state);
+/// Handles an update sent to the dialog, the quote is returned once the last answer is given.
+pub fn step(state: &mut FormState, update: &str) -> Result<DialogResult, String> {
+    let update: Update = serde_json::from_str(update)
+        .map_err(|err| format!("Update JSON deserialization failed: {}", err))?;
+    let api = state.settings.api()?;
+    let form = form(&state.settings);
+    let quote = form.handle_update(state, &api, &update)?;
+    Ok(DialogResult { quote, confirmation: form.confirmation(state) })
 }
 
-fn handle_update(state: &mut State, api: &Api, update: &Update) -> Result<DialogResult, String> {
-    let dialog_state = &state.dialog_state;
-    println!("Dialog state: {:?}", dialog_state);
-    match dialog_state {
-        DialogState::Started => {
-            if let UpdateContent::Message(ref message) = update.content {
-                advance_dialog_and_send_message(api, message.chat.id, state, Event::Start);
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterText => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    advance_dialog_and_send_message(api, message.chat.id, state, Event::ProvideText(text.clone()));
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterTitle(_) => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    advance_dialog_and_send_message(api, message.chat.id, state, Event::ProvideTitle(text.clone()));
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::EnterAuthor(_, _) => {
-            if let UpdateContent::Message(ref message) = update.content {
-                if let Some(ref text) = message.text {
-                    let current_state = advance_dialog_state(state, Event::ProvideAuthor(text.clone()));
-                    if let DialogState::Completed(ref text, ref title, ref author) = current_state {
-                        send_dialog_message(api, message.chat.id, &current_state);
-                        return Ok(DialogResult {
-                            quote: Some(Quote {
-                                text: text.clone(),
-                                title: title.clone(),
-                                author: author.clone(),
-                            })
-                        });
-                    }
-                }
-            }
-            EMPTY_RESULT
-        }
-        DialogState::Completed(ref text, ref title, ref author) => {
-            Ok(DialogResult {
-                quote: Some(Quote {
-                    text: text.clone(),
-                    title: title.clone(),
-                    author: author.clone(),
-                })
-            })
-        }
-    }
+/// Starts the dialog prefilled with the answers of an existing quote.
+pub fn edit(state: &mut FormState, chat_id: i64, quote: Quote) -> Result<DialogResult, String> {
+    let api = state.settings.api()?;
+    form(&state.settings).edit(state, &api, chat_id, values(quote));
+    Ok(DialogResult { quote: None, confirmation: None })
 }
 
-fn send_dialog_message(api: &Api, chat_id: i64, dialog_state: &DialogState) {
-    if let Some(message) = dialog_state.message() {
-        send_message(api, chat_id, &message);
-    }
+/// The state of the form as JSON, for inspecting a running dialog.
+pub fn state(state: &FormState) -> Result<String, String> {
+    serde_json::to_string(state)
+        .map_err(|err| format!("JSON serialization failed: {}", err))
 }
 
+#[cfg(target_arch = "wasm32")]
 impl Guest for Component {
     fn step(update: String) -> Result<DialogResult, String> {
-        with_state(|state| {
-            let update: Update = serde_json::from_str(&update)
-                .map_err(|err| format!("Update JSON deserialization failed: {}", err))?;
-            let api = Api::new(TELEGRAM_TOKEN.as_str());
-            handle_update(state, &api, &update)
-        })
+        with_state(|state| step(state, &update))
+    }
+
+    fn edit(chat_id: i64, quote: Quote) -> Result<DialogResult, String> {
+        with_state(|state| edit(state, chat_id, quote))
+    }
+
+    fn state() -> Result<String, String> {
+        with_state(|state| crate::state(state))
     }
 }
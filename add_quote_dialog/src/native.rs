@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+// The records of `wit/add-quote-dialog.wit`, serialized like Golem does in its JSON API
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DialogResult {
+    pub quote: Option<Quote>,
+    // Sent by the bot once the quote is saved
+    pub confirmation: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Quote {
+    pub text: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub date: Option<String>,
+}
@@ -0,0 +1,106 @@
+#[cfg(target_arch = "wasm32")]
+cargo_component_bindings::generate!();
+#[cfg(target_arch = "wasm32")]
+use crate::bindings::exports::golem::template::api::*;
+
+// Natively the dialog is called in-process, with the records of the WIT interface defined in `native`
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
+
+use dialog_engine::{DialogSettings, Field, Form, FormState, Value, Values, parse_number, t};
+use frankenstein::Update;
+#[cfg(target_arch = "wasm32")]
+use once_cell::sync::Lazy;
+
+#[cfg(target_arch = "wasm32")]
+struct Component;
+
+/// This holds the state of our application.
+/// It is a global variable, which Rust doesn't like, so
+/// we use `with_state` to access or update the global variable, so we
+/// can avoid `unsafe` noise.
+#[cfg(target_arch = "wasm32")]
+static mut STATE: Lazy<FormState> = Lazy::new(|| FormState::new(DialogSettings::from_env()));
+
+#[cfg(target_arch = "wasm32")]
+fn with_state<T>(f: impl FnOnce(&mut FormState) -> T) -> T {
+    unsafe { f(&mut *STATE) }
+}
+
+fn validate_seasons(value: &Value, settings: &DialogSettings) -> Result<(), String> {
+    match value {
+        Value::Number(seasons) if *seasons >= 1 && *seasons <= 100 => Ok(()),
+        _ => Err(t!(settings.language, "series.seasons_error")),
+    }
+}
+
+fn form(settings: &DialogSettings) -> Form<Series> {
+    let language = settings.language;
+    Form {
+        fields: vec![
+            Field::text("title", &t!(language, "prompt.title")),
+            Field::new("seasons", &t!(language, "series.seasons"), parse_number).validate(validate_seasons),
+            Field::rating("rating", settings),
+        ],
+        build: |values| Series {
+            title: values.text("title"),
+            seasons: values.number("seasons"),
+            rating: values.number("rating"),
+        },
+        added: |series, settings| describe("series.added", series, settings),
+        updated: |series, settings| describe("series.updated", series, settings),
+    }
+}
+
+fn describe(key: &str, series: &Series, settings: &DialogSettings) -> String {
+    let language = settings.language;
+    let seasons = language.count("series.seasons_watched", series.seasons as usize);
+    t!(language, key, title = series.title, seasons = seasons, rating = settings.rating_scale.format(series.rating))
+}
+
+fn values(series: Series) -> Values {
+    Values::new()
+        .with("title", series.title)
+        .with("seasons", series.seasons)
+        .with("rating", series.rating)
+}
+
+/// Handles an update sent to the dialog, the series is returned once the last answer is given.
+pub fn step(state: &mut FormState, update: &str) -> Result<DialogResult, String> {
+    let update: Update = serde_json::from_str(update)
+        .map_err(|err| format!("Update JSON deserialization failed: {}", err))?;
+    let api = state.settings.api()?;
+    let form = form(&state.settings);
+    let series = form.handle_update(state, &api, &update)?;
+    Ok(DialogResult { series, confirmation: form.confirmation(state) })
+}
+
+/// Starts the dialog prefilled with the answers of an existing series.
+pub fn edit(state: &mut FormState, chat_id: i64, series: Series) -> Result<DialogResult, String> {
+    let api = state.settings.api()?;
+    form(&state.settings).edit(state, &api, chat_id, values(series));
+    Ok(DialogResult { series: None, confirmation: None })
+}
+
+/// The state of the form as JSON, for inspecting a running dialog.
+pub fn state(state: &FormState) -> Result<String, String> {
+    serde_json::to_string(state)
+        .map_err(|err| format!("JSON serialization failed: {}", err))
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Guest for Component {
+    fn step(update: String) -> Result<DialogResult, String> {
+        with_state(|state| step(state, &update))
+    }
+
+    fn edit(chat_id: i64, series: Series) -> Result<DialogResult, String> {
+        with_state(|state| edit(state, chat_id, series))
+    }
+
+    fn state() -> Result<String, String> {
+        with_state(|state| crate::state(state))
+    }
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+// The records of `wit/add-series-dialog.wit`, serialized like Golem does in its JSON API
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DialogResult {
+    pub series: Option<Series>,
+    // Sent by the bot once the series is saved
+    pub confirmation: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Series {
+    pub title: String,
+    pub seasons: u32,
+    // Score out of 100, independent of the rating scale of the user
+    pub rating: u32,
+}
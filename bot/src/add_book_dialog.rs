@@ -1,5 +1,6 @@
-use crate::domain::{Book, DialogType, ResultCaseInsensitive, State};
+use crate::domain::{Book, DialogType, Entry, ResultCaseInsensitive, State};
 use crate::dialogs::{dialog_step, dispose_dialog};
+use crate::duplicates::save_or_confirm;
 
 use frankenstein::Update;
 use serde::Deserialize;
@@ -8,19 +9,21 @@ use uuid::Uuid;
 #[derive(Deserialize, Debug)]
 pub struct AddBookDialogResult {
     book: Option<Book>,
+    confirmation: Option<String>,
 }
 
-pub fn add_book_dialog_step(state: &mut State, user_id: u64, dialog_id: Uuid, invocation_key: String, update: &Update) -> Result<(), String> {
+pub fn add_book_dialog_step(state: &mut State, user_id: u64, dialog_id: Uuid, update: &Update) -> Result<(), String> {
     let first_result = dialog_step::<ResultCaseInsensitive<AddBookDialogResult, String>>(
-        DialogType::AddBook.template(), dialog_id, invocation_key, update,
+        state, DialogType::AddBook, dialog_id, update,
     )?;
     println!("{:?}", first_result);
     match first_result {
         ResultCaseInsensitive::Ok(book_opt) => {
-            // If the book exists, push it to the state and dispose of the dialog
+            // If the book exists, save it to the state and dispose of the dialog
             if let Some(book) = book_opt.book {
-                state.books.entry(user_id).or_insert(vec![]).push(book);
-                dispose_dialog(state, user_id, DialogType::AddBook.template(), dialog_id);
+                let edit_index = state.dialogs.get(&user_id).and_then(|dialog| dialog.edit_index);
+                save_or_confirm(state, user_id, update, Entry::Book(book), edit_index, book_opt.confirmation);
+                dispose_dialog(state, user_id, DialogType::AddBook, dialog_id);
             }
             Ok(())
         }
@@ -1,5 +1,6 @@
-use crate::domain::{Movie, DialogType, ResultCaseInsensitive, State};
+use crate::domain::{Movie, DialogType, Entry, ResultCaseInsensitive, State};
 use crate::dialogs::{dialog_step, dispose_dialog};
+use crate::duplicates::save_or_confirm;
 
 use frankenstein::Update;
 use serde::Deserialize;
@@ -7,20 +8,22 @@ use uuid::Uuid;
 
 #[derive(Deserialize, Debug)]
 pub struct AddMovieDialogResult {
-    movie: Option<Movie>
+    movie: Option<Movie>,
+    confirmation: Option<String>,
 }
 
-pub fn add_movie_dialog_step(state: &mut State, user_id: u64, dialog_id: Uuid, invocation_key: String, update: &Update) -> Result<(), String> {
+pub fn add_movie_dialog_step(state: &mut State, user_id: u64, dialog_id: Uuid, update: &Update) -> Result<(), String> {
     let first_result = dialog_step::<ResultCaseInsensitive<AddMovieDialogResult, String>>(
-        DialogType::AddMovie.template(), dialog_id, invocation_key, update
+        state, DialogType::AddMovie, dialog_id, update
     )?;
     println!("{:?}", first_result);
     match first_result {
         ResultCaseInsensitive::Ok(movie_opt) => {
-            // If the movie exists, push it to the state and dispose of the dialog
+            // If the movie exists, save it to the state and dispose of the dialog
             if let Some(movie) = movie_opt.movie {
-                state.movies.entry(user_id).or_insert(vec![]).push(movie);
-                dispose_dialog(state, user_id, DialogType::AddMovie.template(), dialog_id);
+                let edit_index = state.dialogs.get(&user_id).and_then(|dialog| dialog.edit_index);
+                save_or_confirm(state, user_id, update, Entry::Movie(movie), edit_index, movie_opt.confirmation);
+                dispose_dialog(state, user_id, DialogType::AddMovie, dialog_id);
             }
             Ok(())
         },
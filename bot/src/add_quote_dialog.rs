@@ -1,5 +1,6 @@
-use crate::domain::{Quote, DialogType, ResultCaseInsensitive, State};
+use crate::domain::{Quote, DialogType, Entry, ResultCaseInsensitive, State};
 use crate::dialogs::{dialog_step, dispose_dialog};
+use crate::duplicates::save_or_confirm;
 
 use frankenstein::Update;
 use serde::Deserialize;
@@ -8,19 +9,21 @@ use uuid::Uuid;
 #[derive(Deserialize, Debug)]
 pub struct AddQuoteDialogResult {
     quote: Option<Quote>,
+    confirmation: Option<String>,
 }
 
-pub fn add_quote_dialog_step(state: &mut State, user_id: u64, dialog_id: Uuid, invocation_key: String, update: &Update) -> Result<(), String> {
+pub fn add_quote_dialog_step(state: &mut State, user_id: u64, dialog_id: Uuid, update: &Update) -> Result<(), String> {
     let first_result = dialog_step::<ResultCaseInsensitive<AddQuoteDialogResult, String>>(
-        DialogType::AddQuote.template(), dialog_id, invocation_key, update,
+        state, DialogType::AddQuote, dialog_id, update,
     )?;
     println!("{:?}", first_result);
     match first_result {
         ResultCaseInsensitive::Ok(quote_opt) => {
-            // If the quote exists, push it to the state and dispose of the dialog
+            // If the quote exists, save it to the state and dispose of the dialog
             if let Some(quote) = quote_opt.quote {
-                state.quotes.entry(user_id).or_insert(vec![]).push(quote);
-                dispose_dialog(state, user_id, DialogType::AddQuote.template(), dialog_id);
+                let edit_index = state.dialogs.get(&user_id).and_then(|dialog| dialog.edit_index);
+                save_or_confirm(state, user_id, update, Entry::Quote(quote), edit_index, quote_opt.confirmation);
+                dispose_dialog(state, user_id, DialogType::AddQuote, dialog_id);
             }
             Ok(())
         }
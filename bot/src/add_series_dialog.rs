@@ -0,0 +1,34 @@
+use crate::domain::{Series, DialogType, Entry, ResultCaseInsensitive, State};
+use crate::dialogs::{dialog_step, dispose_dialog};
+use crate::duplicates::save_or_confirm;
+
+use frankenstein::Update;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug)]
+pub struct AddSeriesDialogResult {
+    series: Option<Series>,
+    confirmation: Option<String>,
+}
+
+pub fn add_series_dialog_step(state: &mut State, user_id: u64, dialog_id: Uuid, update: &Update) -> Result<(), String> {
+    let first_result = dialog_step::<ResultCaseInsensitive<AddSeriesDialogResult, String>>(
+        state, DialogType::AddSeries, dialog_id, update
+    )?;
+    println!("{:?}", first_result);
+    match first_result {
+        ResultCaseInsensitive::Ok(series_opt) => {
+            // If the series exists, save it to the state and dispose of the dialog
+            if let Some(series) = series_opt.series {
+                let edit_index = state.dialogs.get(&user_id).and_then(|dialog| dialog.edit_index);
+                save_or_confirm(state, user_id, update, Entry::Series(series), edit_index, series_opt.confirmation);
+                dispose_dialog(state, user_id, DialogType::AddSeries, dialog_id);
+            }
+            Ok(())
+        },
+        ResultCaseInsensitive::Err(err) => {
+            Err(format!("Error in dialog step: {}", err))
+        }
+    }
+}
@@ -1,24 +1,38 @@
 use crate::add_book_dialog::*;
 use crate::add_movie_dialog::*;
 use crate::add_quote_dialog::*;
-use crate::domain::{Dialog, DialogType, State};
-use crate::dialogs::{create_dialog, dispose_dialog};
-use crate::env::TELEGRAM_TOKEN;
-use crate::workers::get_invocation_key;
+use crate::add_series_dialog::*;
+use crate::callbacks::{CallbackAction, confirm_delete_keyboard, export_keyboard, fingerprint, language_keyboard, picker_keyboard, rating_scale_keyboard, shelves_keyboard};
+use crate::config::config;
+use crate::domain::{Dialog, DialogType, MediaKind, ReadingStatus, State, WatchlistItem};
+use crate::dialogs::{create_dialog, create_edit_dialog, create_watched_dialog, dispose_dialog, log_dialog_state};
+use crate::duplicates::resolve_duplicate;
+use crate::export::{ExportFormat, export};
+use crate::import::import_document;
+use crate::lists::{render_page, render_watchlist, watchlist_page};
+use crate::search::{normalize, render_results};
+use crate::stats::render_stats;
 
-use frankenstein::{AllowedUpdate, CallbackQuery, GetUpdatesParams, Message, Update, UpdateContent};
+use dialog_engine::{Language, t, today, validate_year};
+use frankenstein::{AllowedUpdate, CallbackQuery, GetUpdatesParams, InlineKeyboardMarkup, Message, Update, UpdateContent};
 use telegram_api::*;
 
+/// The kinds of updates the bot handles, for both `get_updates` and the webhook.
+pub fn allowed_updates() -> Vec<AllowedUpdate> {
+    vec![
+        AllowedUpdate::Message,
+        AllowedUpdate::CallbackQuery,
+    ]
+}
+
+/// Long polling: receives updates with `get_updates` forever.
 pub fn handle_updates(state: &mut State) {
-    let api = Api::new(TELEGRAM_TOKEN.as_str());
+    let api = config().telegram_api();
     let mut update_params = GetUpdatesParams {
         offset: None,
         limit: None,
-        timeout: Some(10u32),
-        allowed_updates: Some(vec![
-            AllowedUpdate::Message,
-            AllowedUpdate::CallbackQuery,
-        ]),
+        timeout: Some(config().polling_timeout),
+        allowed_updates: Some(allowed_updates()),
     };
     loop {
         let result = &api.get_updates(&update_params);
@@ -26,15 +40,7 @@ pub fn handle_updates(state: &mut State) {
             Ok(updates) => {
                 for update in &updates.result {
                     update_params.offset = Some((update.update_id + 1).into());
-                    match update.content {
-                        UpdateContent::Message(ref message) => {
-                            on_message(state, &api, message, &update);
-                        }
-                        UpdateContent::CallbackQuery(ref callback_query) => {
-                            on_callback_query(state, callback_query, &update);
-                        }
-                        _ => (),
-                    };
+                    handle_update(state, &api, update);
                 }
             }
             Err(e) => {
@@ -44,16 +50,35 @@ pub fn handle_updates(state: &mut State) {
     };
 }
 
+/// Handles a single update, received by polling or delivered to the webhook.
+pub fn handle_update(state: &mut State, api: &Api, update: &Update) {
+    let user_id = match update.content {
+        UpdateContent::Message(ref message) => {
+            on_message(state, api, message, update);
+            message.from.as_ref().map(|user| user.id)
+        }
+        UpdateContent::CallbackQuery(ref callback_query) => {
+            on_callback_query(state, api, callback_query, update);
+            Some(callback_query.from.id)
+        }
+        _ => None,
+    };
+    if let Some(user_id) = user_id {
+        state.save(user_id);
+    }
+}
+
 fn on_message(state: &mut State, api: &Api, message: &Message, update: &Update) -> () {
     // if user is in dialog state, send message to dialog worker
     // else if user is not in dialog state, handle message
     if let Some(user) = &message.from.as_ref() {
         let user_id = user.id;
+        state.set_telegram_language(user_id, user.language_code.as_deref());
         if let Some(dialog) = state.dialogs.get(&user_id).cloned() {
             if let Some(text) = &message.text {
                 if text.starts_with("/reset") {
-                    dispose_dialog(state, user_id, dialog.dialog_type.template(), dialog.dialog_id);
-                    send_message(api, message.chat.id, "Dialog reset");
+                    dispose_dialog(state, user_id, dialog.dialog_type, dialog.dialog_id);
+                    send_message(api, message.chat.id, &t!(state.language(user_id), "dialog.reset"));
                     return;
                 }
             }
@@ -61,58 +86,343 @@ fn on_message(state: &mut State, api: &Api, message: &Message, update: &Update)
             return;  // Early return to avoid going to the next block
         }
         // Fallback if we didn't return early
+        if let Some(document) = &message.document {
+            import_document(state, api, message.chat.id, user_id, document);
+            return;
+        }
         handle_commands(state, api, message, update, user_id);
     }
 
 }
 
-fn on_callback_query(state: &mut State, cb: &CallbackQuery, update: &Update) -> () {
+fn on_callback_query(state: &mut State, api: &Api, cb: &CallbackQuery, update: &Update) -> () {
     let user_id = cb.from.id;
+    state.set_telegram_language(user_id, cb.from.language_code.as_deref());
     if let Some(dialog) = state.dialogs.get(&user_id).cloned() {
         dispatch_dialog(state, update, user_id, dialog);
+        return;
+    }
+    let language = state.language(user_id);
+    let gone = t!(language, "item.gone");
+    // Not in a dialog, so the callback comes from one of the bot's own keyboards
+    let action = cb.data.as_ref().and_then(|data| CallbackAction::parse(data));
+    let chat_id = cb.message.as_ref().map_or(user_id as i64, |message| message.chat.id);
+    match action {
+        Some(CallbackAction::Edit(kind, index, expected)) => {
+            match state.label(user_id, kind, index).filter(|label| fingerprint(label) == expected) {
+                Some(_) => {
+                    answer_callback_query(api, &cb.id, None);
+                    start_edit_dialog(state, api, chat_id, user_id, kind, index);
+                }
+                None => answer_callback_query(api, &cb.id, Some(&gone)),
+            }
+        }
+        Some(CallbackAction::EditPage(kind, page)) => {
+            answer_callback_query(api, &cb.id, None);
+            if let Some(message) = &cb.message {
+                match render_edit_picker(state, user_id, kind, page) {
+                    Some((text, keyboard)) => edit_message_text(api, chat_id, message.message_id, &text, Some(keyboard)),
+                    None => edit_message_text(api, chat_id, message.message_id, &empty_list_text(language, kind, None), None),
+                }
+            }
+        }
+        Some(CallbackAction::Delete(kind, index, expected)) => {
+            match state.label(user_id, kind, index).filter(|label| fingerprint(label) == expected) {
+                Some(label) => {
+                    answer_callback_query(api, &cb.id, None);
+                    let keyboard = confirm_delete_keyboard(language, kind, index, expected);
+                    send_message_with_keyboard(api, chat_id, &t!(language, "delete.confirm", item = label), keyboard);
+                }
+                None => answer_callback_query(api, &cb.id, Some(&gone)),
+            }
+        }
+        Some(CallbackAction::ConfirmDelete(kind, index, expected)) => {
+            let matches = state.label(user_id, kind, index).is_some_and(|label| fingerprint(&label) == expected);
+            let removed = if matches { state.remove_entry(user_id, kind, index) } else { None };
+            match removed {
+                Some(label) => {
+                    answer_callback_query(api, &cb.id, Some(&t!(language, "delete.done")));
+                    if let Some(message) = &cb.message {
+                        edit_message_text(api, chat_id, message.message_id, &t!(language, "delete.deleted", item = label), None);
+                    }
+                }
+                None => answer_callback_query(api, &cb.id, Some(&gone)),
+            }
+        }
+        Some(CallbackAction::CancelDelete) => {
+            answer_callback_query(api, &cb.id, None);
+            if let Some(message) = &cb.message {
+                edit_message_text(api, chat_id, message.message_id, &t!(language, "delete.cancelled"), None);
+            }
+        }
+        Some(CallbackAction::Page(kind, shelf, page)) => {
+            answer_callback_query(api, &cb.id, None);
+            if let Some(message) = &cb.message {
+                match render_page(state, user_id, kind, shelf, page) {
+                    Some((text, keyboard)) => edit_message_text(api, chat_id, message.message_id, &text, Some(keyboard)),
+                    None => edit_message_text(api, chat_id, message.message_id, &empty_list_text(language, kind, shelf), None),
+                }
+            }
+        }
+        Some(CallbackAction::Export(format)) => {
+            answer_callback_query(api, &cb.id, None);
+            send_export(state, api, chat_id, user_id, format);
+        }
+        Some(CallbackAction::PickShelf(index, expected)) => {
+            match state.label(user_id, MediaKind::Book, index).filter(|label| fingerprint(label) == expected) {
+                Some(label) => {
+                    answer_callback_query(api, &cb.id, None);
+                    let keyboard = shelves_keyboard(language, index, expected);
+                    send_message_with_keyboard(api, chat_id, &t!(language, "shelf.move_to", item = label), keyboard);
+                }
+                None => answer_callback_query(api, &cb.id, Some(&gone)),
+            }
+        }
+        Some(CallbackAction::PickShelfPage(page)) => {
+            answer_callback_query(api, &cb.id, None);
+            if let Some(message) = &cb.message {
+                match render_shelf_picker(state, user_id, page) {
+                    Some((text, keyboard)) => edit_message_text(api, chat_id, message.message_id, &text, Some(keyboard)),
+                    None => edit_message_text(api, chat_id, message.message_id, &empty_list_text(language, MediaKind::Book, None), None),
+                }
+            }
+        }
+        Some(CallbackAction::MoveToShelf(index, expected, shelf)) => {
+            let matches = state.label(user_id, MediaKind::Book, index).is_some_and(|label| fingerprint(&label) == expected);
+            let moved = if matches { move_book(state, user_id, index, shelf) } else { None };
+            match moved {
+                Some(text) => {
+                    answer_callback_query(api, &cb.id, Some(&t!(language, "shelf.moved")));
+                    if let Some(message) = &cb.message {
+                        edit_message_text(api, chat_id, message.message_id, &text, None);
+                    }
+                }
+                None => answer_callback_query(api, &cb.id, Some(&gone)),
+            }
+        }
+        Some(CallbackAction::Watched(index, expected)) => {
+            match watchlist_item(state, user_id, index, expected) {
+                Some(item) => {
+                    answer_callback_query(api, &cb.id, None);
+                    let result = create_watched_dialog(state, user_id, index, chat_id, &item);
+                    if let Err(err) = result {
+                        println!("Error starting watched dialog: {}", err);
+                    };
+                }
+                None => answer_callback_query(api, &cb.id, Some(&gone)),
+            }
+        }
+        Some(CallbackAction::Unwatch(index, expected)) => {
+            match watchlist_item(state, user_id, index, expected) {
+                Some(_) => {
+                    if let Some(watchlist) = state.watchlist.get_mut(&user_id) {
+                        watchlist.remove(index);
+                    }
+                    answer_callback_query(api, &cb.id, Some(&t!(language, "watchlist.removed")));
+                    if let Some(message) = &cb.message {
+                        // Stay on the page of the removed title, the titles after it move up
+                        let page = watchlist_page(state, user_id, index);
+                        match render_watchlist(state, user_id, page) {
+                            Some((text, keyboard)) => edit_message_text(api, chat_id, message.message_id, &text, Some(keyboard)),
+                            None => edit_message_text(api, chat_id, message.message_id, &t!(language, "watchlist.empty"), None),
+                        }
+                    }
+                }
+                None => answer_callback_query(api, &cb.id, Some(&gone)),
+            }
+        }
+        Some(CallbackAction::WatchlistPage(page)) => {
+            answer_callback_query(api, &cb.id, None);
+            if let Some(message) = &cb.message {
+                match render_watchlist(state, user_id, page) {
+                    Some((text, keyboard)) => edit_message_text(api, chat_id, message.message_id, &text, Some(keyboard)),
+                    None => edit_message_text(api, chat_id, message.message_id, &t!(language, "watchlist.empty"), None),
+                }
+            }
+        }
+        Some(CallbackAction::SetRatingScale(scale)) => {
+            state.settings.entry(user_id).or_default().rating_scale = scale;
+            answer_callback_query(api, &cb.id, None);
+            if let Some(message) = &cb.message {
+                edit_message_text(api, chat_id, message.message_id, &t!(language, "scale.changed", scale = scale.name(language)), None);
+            }
+        }
+        Some(CallbackAction::SetLanguage(language)) => {
+            state.settings.entry(user_id).or_default().language = Some(language);
+            answer_callback_query(api, &cb.id, None);
+            if let Some(message) = &cb.message {
+                edit_message_text(api, chat_id, message.message_id, &t!(language, "language.changed"), None);
+            }
+        }
+        Some(CallbackAction::ResolveDuplicate(resolution, expected)) => {
+            match resolve_duplicate(state, user_id, resolution, expected) {
+                Some(text) => {
+                    answer_callback_query(api, &cb.id, None);
+                    match &cb.message {
+                        Some(message) => edit_message_text(api, chat_id, message.message_id, &text, None),
+                        None => send_message(api, chat_id, &text),
+                    }
+                }
+                None => answer_callback_query(api, &cb.id, Some(&t!(language, "duplicate.answered"))),
+            }
+        }
+        None => answer_callback_query(api, &cb.id, None),
     }
 }
 
-fn dispatch_dialog(state: &mut State, update: &Update, user_id: u64, dialog: Dialog) {
-    let invocation_key_result = get_invocation_key(dialog.dialog_id, dialog.dialog_type.template());
-
-    let invocation_key = match invocation_key_result {
-        Ok(key) => key,
+fn send_export(state: &State, api: &Api, chat_id: i64, user_id: u64, format: ExportFormat) {
+    let language = state.language(user_id);
+    let library = state.library(user_id);
+    if library.is_empty() {
+        send_message(api, chat_id, &t!(language, "library.empty"));
+        return;
+    }
+    match export(&library, format) {
+        Ok(content) => send_document(api, chat_id, &format.file_name(), content, &t!(language, "export.caption")),
         Err(err) => {
-            println!("Error getting invocation key: {}", err);
-            return;
+            println!("Error exporting library: {}", err);
+            send_message(api, chat_id, &t!(language, "export.failed"));
         }
-    };
+    }
+}
 
-    match dialog.dialog_type {
-        DialogType::AddBook => {
-            let result = add_book_dialog_step(state, user_id, dialog.dialog_id, invocation_key, update);
-            if let Err(err) = result {
-                println!("Error in add_book_dialog_step: {}", err);
-            };
-        },
-        DialogType::AddMovie => {
-            let result = add_movie_dialog_step(state, user_id, dialog.dialog_id, invocation_key, update);
-            if let Err(err) = result {
-                println!("Error in add_movie_dialog_step: {}", err);
-            };
-        },
-        DialogType::AddQuote => {
-            let result = add_quote_dialog_step(state, user_id, dialog.dialog_id, invocation_key, update);
+fn start_edit_dialog(state: &mut State, api: &Api, chat_id: i64, user_id: u64, kind: MediaKind, index: usize) {
+    match state.entry_json(user_id, kind, index) {
+        Some(_) if !kind.dialog_type().available() => send_message(api, chat_id, &t!(state.language(user_id), "dialog.unavailable")),
+        Some(entry) => {
+            let result = create_edit_dialog(state, user_id, kind.dialog_type(), index, chat_id, entry);
             if let Err(err) = result {
-                println!("Error in add_quote_dialog_step: {}", err);
+                println!("Error starting edit {} dialog: {}", kind.code(), err);
             };
         }
+        None => send_message(api, chat_id, &t!(state.language(user_id), "item.gone")),
     }
 }
 
+/// Puts the book on another shelf and returns the confirmation text.
+fn move_book(state: &mut State, user_id: u64, index: usize, shelf: ReadingStatus) -> Option<String> {
+    let language = state.language(user_id);
+    let book = state.books.get_mut(&user_id).and_then(|books| books.get_mut(index))?;
+    book.status = shelf;
+    if shelf != ReadingStatus::Finished {
+        // Only finished books have a rating and a date read
+        book.rating = None;
+        book.date = None;
+    } else if book.date.is_none() {
+        book.date = Some(today());
+    }
+    if shelf == ReadingStatus::Finished && book.rating.is_none() {
+        Some(t!(language, "shelf.moved_unrated", item = book.label(), shelf = shelf.name(language)))
+    } else {
+        Some(t!(language, "shelf.moved_to", item = book.label(), shelf = shelf.name(language)))
+    }
+}
+
+/// The watchlist entry at `index`, if it's still the one the button was created for.
+fn watchlist_item(state: &State, user_id: u64, index: usize, expected: u32) -> Option<WatchlistItem> {
+    state.watchlist.get(&user_id)
+        .and_then(|watchlist| watchlist.get(index))
+        .filter(|item| fingerprint(&item.label()) == expected)
+        .cloned()
+}
+
+/// Parses "Title" or "Title (YYYY)".
+fn parse_watchlist_item(language: Language, text: &str) -> Result<WatchlistItem, String> {
+    let text = text.trim();
+    let year = text.strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once('('))
+        .map(|(title, year)| (title.trim(), year.trim().to_string()));
+    let (title, year) = match year {
+        Some((title, year)) => (title, Some(validate_year(&year).map_err(|key| t!(language, key))?)),
+        None => (text, None),
+    };
+    if title.is_empty() {
+        return Err(t!(language, "watchlist.title_required"));
+    }
+    Ok(WatchlistItem { title: title.to_string(), year })
+}
+
+fn add_to_watchlist(state: &mut State, api: &Api, chat_id: i64, user_id: u64, text: &str) {
+    let language = state.language(user_id);
+    let item = match parse_watchlist_item(language, text) {
+        Ok(item) => item,
+        Err(err) => {
+            send_message(api, chat_id, &err);
+            return;
+        }
+    };
+    let watchlist = state.watchlist.entry(user_id).or_insert(vec![]);
+    if watchlist.iter().any(|other| normalize(&other.title) == normalize(&item.title) && other.year == item.year) {
+        send_message(api, chat_id, &t!(language, "watchlist.already", item = item.label()));
+        return;
+    }
+    send_message(api, chat_id, &t!(language, "watchlist.added", item = item.label()));
+    watchlist.push(item);
+}
+
+fn send_watchlist(state: &State, api: &Api, chat_id: i64, user_id: u64) {
+    match render_watchlist(state, user_id, 0) {
+        Some((text, keyboard)) => send_message_with_keyboard(api, chat_id, &text, keyboard),
+        None => send_message(api, chat_id, &t!(state.language(user_id), "watchlist.empty_hint")),
+    }
+}
+
+/// Renders one page of the books to pick for moving to another shelf, `None` if there are none.
+fn render_shelf_picker(state: &State, user_id: u64, page: usize) -> Option<(String, InlineKeyboardMarkup)> {
+    let language = state.language(user_id);
+    let labels = state.labels(user_id, MediaKind::Book);
+    if labels.is_empty() {
+        return None;
+    }
+    let keyboard = picker_keyboard(language, &labels, page, |index| CallbackAction::PickShelf(index, fingerprint(&labels[index])), CallbackAction::PickShelfPage);
+    Some((t!(language, "shelf.choose_book"), keyboard))
+}
+
+fn send_shelf_picker(state: &State, api: &Api, chat_id: i64, user_id: u64) {
+    match render_shelf_picker(state, user_id, 0) {
+        Some((text, keyboard)) => send_message_with_keyboard(api, chat_id, &text, keyboard),
+        None => send_message(api, chat_id, &empty_list_text(state.language(user_id), MediaKind::Book, None)),
+    }
+}
+
+/// Renders one page of the entries to pick for editing, `None` if there are none.
+fn render_edit_picker(state: &State, user_id: u64, kind: MediaKind, page: usize) -> Option<(String, InlineKeyboardMarkup)> {
+    let language = state.language(user_id);
+    let labels = state.labels(user_id, kind);
+    if labels.is_empty() {
+        return None;
+    }
+    let keyboard = picker_keyboard(language, &labels, page, |index| CallbackAction::Edit(kind, index, fingerprint(&labels[index])), |page| CallbackAction::EditPage(kind, page));
+    Some((t!(language, &format!("edit.choose.{}", kind.code())), keyboard))
+}
+
+fn send_edit_picker(state: &State, api: &Api, chat_id: i64, user_id: u64, kind: MediaKind) {
+    match render_edit_picker(state, user_id, kind, 0) {
+        Some((text, keyboard)) => send_message_with_keyboard(api, chat_id, &text, keyboard),
+        None => send_message(api, chat_id, &empty_list_text(state.language(user_id), kind, None)),
+    }
+}
+
+fn dispatch_dialog(state: &mut State, update: &Update, user_id: u64, dialog: Dialog) {
+    let (step, result) = match dialog.dialog_type {
+        DialogType::AddBook => ("add_book_dialog_step", add_book_dialog_step(state, user_id, dialog.dialog_id, update)),
+        DialogType::AddMovie => ("add_movie_dialog_step", add_movie_dialog_step(state, user_id, dialog.dialog_id, update)),
+        DialogType::AddQuote => ("add_quote_dialog_step", add_quote_dialog_step(state, user_id, dialog.dialog_id, update)),
+        DialogType::AddSeries => ("add_series_dialog_step", add_series_dialog_step(state, user_id, dialog.dialog_id, update)),
+    };
+    if let Err(err) = result {
+        println!("Error in {}: {}", step, err);
+        log_dialog_state(state, &dialog);
+    };
+}
+
 
 fn handle_commands(state: &mut State, api: &Api, message: &Message, update: &Update, user_id: u64) {
     if let Some(text) = &message.text {
         let chat_id = message.chat.id;
+        let language = state.language(user_id);
         if text.starts_with("/start") {
-            let text = "Use /add_book, /add_movie or /add_quote to add a new item. Use /books, /movies or /quotes to list your items.";
-            send_message(api,  chat_id, &text);
+            send_message(api, chat_id, &t!(language, "help"));
         } else if text.starts_with("/add_book") {
             let result = create_dialog(state, user_id, DialogType::AddBook, update, add_book_dialog_step);
             if let Err(err) = result {
@@ -128,36 +438,80 @@ fn handle_commands(state: &mut State, api: &Api, message: &Message, update: &Upd
             if let Err(err) = result {
                 println!("Error starting add quote dialog: {}", err);
             };
+        } else if text.starts_with("/add_series") && !DialogType::AddSeries.available() {
+            send_message(api, chat_id, &t!(language, "dialog.unavailable"));
+        } else if text.starts_with("/add_series") {
+            let result = create_dialog(state, user_id, DialogType::AddSeries, update, add_series_dialog_step);
+            if let Err(err) = result {
+                println!("Error starting add series dialog: {}", err);
+            };
+        } else if text.starts_with("/edit_book") {
+            send_edit_picker(state, api, chat_id, user_id, MediaKind::Book);
+        } else if text.starts_with("/edit_movie") {
+            send_edit_picker(state, api, chat_id, user_id, MediaKind::Movie);
+        } else if text.starts_with("/edit_quote") {
+            send_edit_picker(state, api, chat_id, user_id, MediaKind::Quote);
+        } else if text.starts_with("/edit_series") {
+            send_edit_picker(state, api, chat_id, user_id, MediaKind::Series);
+        } else if text.starts_with("/search") {
+            // "/search query" or "/search@BotName query"
+            match text.split_once(' ').map(|(_, query)| query.trim()).filter(|query| !query.is_empty()) {
+                Some(query) => send_message(api, chat_id, &render_results(state, user_id, query)),
+                None => send_message(api, chat_id, &t!(language, "search.usage")),
+            }
+        } else if text.starts_with("/stats") {
+            send_message(api, chat_id, &render_stats(state, user_id));
+        } else if text.starts_with("/export") {
+            // "/export" asks for the format, "/export csv" exports right away
+            match text.split_once(' ').and_then(|(_, format)| ExportFormat::from_code(format.trim())) {
+                Some(format) => send_export(state, api, chat_id, user_id, format),
+                None => send_message_with_keyboard(api, chat_id, &t!(language, "export.choose"), export_keyboard()),
+            }
+        } else if text.starts_with("/scale") {
+            let text = t!(language, "scale.choose", scale = state.rating_scale(user_id).name(language));
+            send_message_with_keyboard(api, chat_id, &text, rating_scale_keyboard(language));
+        } else if text.starts_with("/language") {
+            let text = t!(language, "language.choose", language = language.name());
+            send_message_with_keyboard(api, chat_id, &text, language_keyboard());
+        } else if text.starts_with("/shelve") {
+            send_shelf_picker(state, api, chat_id, user_id);
         } else if text.starts_with("/books") {
-            if let Some(books) = state.books.get(&user_id) {
-                let mut text = "Your books:\n".to_string();
-                for book in books {
-                    text.push_str(&format!("{} by {} (rating: {})\n", book.title, book.author, book.rating));
-                }
-                send_message(api, chat_id, &text);
-            } else {
-                send_message(api, chat_id, "You have no books");
+            // "/books" lists every shelf, "/books reading" a single one
+            match text.split_once(' ').map(|(_, shelf)| shelf.trim()).filter(|shelf| !shelf.is_empty()) {
+                Some(shelf) => match ReadingStatus::from_code(shelf) {
+                    Some(shelf) => send_list(state, api, chat_id, user_id, MediaKind::Book, Some(shelf)),
+                    None => send_message(api, chat_id, &t!(language, "books.usage")),
+                },
+                None => send_list(state, api, chat_id, user_id, MediaKind::Book, None),
             }
-        } else if text.starts_with("/movies") {
-            if let Some(movies) = state.movies.get(&user_id) {
-                let mut text = "Your movies:\n".to_string();
-                for movie in movies {
-                    text.push_str(&format!("{} ({}) (rating: {})\n", movie.title, movie.year, movie.rating));
-                }
-                send_message(api, chat_id, &text);
-            } else {
-                send_message(api, chat_id, "You have no movies");
+        } else if text.starts_with("/watchlist") {
+            // "/watchlist" lists the titles, "/watchlist Dune (2021)" adds one
+            match text.split_once(' ').map(|(_, title)| title.trim()).filter(|title| !title.is_empty()) {
+                Some(title) => add_to_watchlist(state, api, chat_id, user_id, title),
+                None => send_watchlist(state, api, chat_id, user_id),
             }
+        } else if text.starts_with("/movies") {
+            send_list(state, api, chat_id, user_id, MediaKind::Movie, None);
         } else if text.starts_with("/quotes") {
-            if let Some(quotes) = state.quotes.get(&user_id) {
-                let mut text = "Your quotes:\n".to_string();
-                for quote in quotes {
-                    text.push_str(&format!("\"{}\" from {} by {}\n\n", quote.text, quote.title, quote.author));
-                }
-                send_message(api, chat_id, &text);
-            } else {
-                send_message(api, chat_id, "You have no quotes");
-            }
+            send_list(state, api, chat_id, user_id, MediaKind::Quote, None);
+        } else if text.starts_with("/series") {
+            send_list(state, api, chat_id, user_id, MediaKind::Series, None);
+        } else if text.starts_with("/config") && config().is_admin(user_id) {
+            send_message(api, chat_id, &format!("{}\n{:#?}", t!(language, "config.title"), config()));
         }
     }
 }
+
+fn empty_list_text(language: Language, kind: MediaKind, shelf: Option<ReadingStatus>) -> String {
+    match shelf {
+        Some(shelf) => t!(language, "list.empty_shelf", shelf = shelf.name(language)),
+        None => t!(language, &format!("list.empty.{}", kind.code())),
+    }
+}
+
+fn send_list(state: &State, api: &Api, chat_id: i64, user_id: u64, kind: MediaKind, shelf: Option<ReadingStatus>) {
+    match render_page(state, user_id, kind, shelf, 0) {
+        Some((text, keyboard)) => send_message_with_keyboard(api, chat_id, &text, keyboard),
+        None => send_message(api, chat_id, &empty_list_text(state.language(user_id), kind, shelf)),
+    }
+}
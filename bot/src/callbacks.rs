@@ -0,0 +1,235 @@
+use crate::config::config;
+use crate::domain::{MediaKind, ReadingStatus};
+use crate::duplicates::DuplicateResolution;
+use crate::export::ExportFormat;
+
+use dialog_engine::{Language, RatingScale, t};
+use frankenstein::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+/// Actions handled by the bot itself, encoded in the `callback_data` of inline buttons.
+/// Telegram limits `callback_data` to 64 bytes, so the encoding is kept short: `edit:book:3`.
+/// Actions on an entry carry a fingerprint of its label, so a button from an outdated list
+/// can't edit or remove an entry that has since moved to the same index.
+#[derive(Debug, PartialEq)]
+pub enum CallbackAction {
+    Edit(MediaKind, usize, u32),
+    EditPage(MediaKind, usize),
+    Delete(MediaKind, usize, u32),
+    ConfirmDelete(MediaKind, usize, u32),
+    CancelDelete,
+    // Books can be listed from a single shelf
+    Page(MediaKind, Option<ReadingStatus>, usize),
+    Export(ExportFormat),
+    PickShelf(usize, u32),
+    PickShelfPage(usize),
+    MoveToShelf(usize, u32, ReadingStatus),
+    Watched(usize, u32),
+    Unwatch(usize, u32),
+    WatchlistPage(usize),
+    SetRatingScale(RatingScale),
+    SetLanguage(Language),
+    // Carries a fingerprint of the new entry, answering an earlier question does nothing
+    ResolveDuplicate(DuplicateResolution, u32),
+}
+
+impl CallbackAction {
+    pub fn parse(data: &str) -> Option<CallbackAction> {
+        let parts: Vec<&str> = data.split(':').collect();
+        match parts.as_slice() {
+            ["edit", kind, index, fingerprint] => {
+                let kind = MediaKind::from_code(kind)?;
+                let index = index.parse::<usize>().ok()?;
+                let fingerprint = u32::from_str_radix(fingerprint, 16).ok()?;
+                Some(CallbackAction::Edit(kind, index, fingerprint))
+            }
+            ["edit_page", kind, page] => {
+                let kind = MediaKind::from_code(kind)?;
+                let page = page.parse::<usize>().ok()?;
+                Some(CallbackAction::EditPage(kind, page))
+            }
+            ["del", kind, index, fingerprint] => {
+                let kind = MediaKind::from_code(kind)?;
+                let index = index.parse::<usize>().ok()?;
+                let fingerprint = u32::from_str_radix(fingerprint, 16).ok()?;
+                Some(CallbackAction::Delete(kind, index, fingerprint))
+            }
+            ["del_yes", kind, index, fingerprint] => {
+                let kind = MediaKind::from_code(kind)?;
+                let index = index.parse::<usize>().ok()?;
+                let fingerprint = u32::from_str_radix(fingerprint, 16).ok()?;
+                Some(CallbackAction::ConfirmDelete(kind, index, fingerprint))
+            }
+            ["del_no"] => Some(CallbackAction::CancelDelete),
+            ["page", kind, page] => {
+                let kind = MediaKind::from_code(kind)?;
+                let page = page.parse::<usize>().ok()?;
+                Some(CallbackAction::Page(kind, None, page))
+            }
+            ["page", kind, page, shelf] => {
+                let kind = MediaKind::from_code(kind)?;
+                let page = page.parse::<usize>().ok()?;
+                let shelf = ReadingStatus::from_code(shelf)?;
+                Some(CallbackAction::Page(kind, Some(shelf), page))
+            }
+            ["export", format] => ExportFormat::from_code(format).map(CallbackAction::Export),
+            ["shelf", index, fingerprint] => {
+                let index = index.parse::<usize>().ok()?;
+                let fingerprint = u32::from_str_radix(fingerprint, 16).ok()?;
+                Some(CallbackAction::PickShelf(index, fingerprint))
+            }
+            ["shelf_page", page] => page.parse::<usize>().ok().map(CallbackAction::PickShelfPage),
+            ["shelve", index, fingerprint, shelf] => {
+                let index = index.parse::<usize>().ok()?;
+                let fingerprint = u32::from_str_radix(fingerprint, 16).ok()?;
+                let shelf = ReadingStatus::from_code(shelf)?;
+                Some(CallbackAction::MoveToShelf(index, fingerprint, shelf))
+            }
+            ["watched", index, fingerprint] => {
+                let index = index.parse::<usize>().ok()?;
+                let fingerprint = u32::from_str_radix(fingerprint, 16).ok()?;
+                Some(CallbackAction::Watched(index, fingerprint))
+            }
+            ["unwatch", index, fingerprint] => {
+                let index = index.parse::<usize>().ok()?;
+                let fingerprint = u32::from_str_radix(fingerprint, 16).ok()?;
+                Some(CallbackAction::Unwatch(index, fingerprint))
+            }
+            ["watchlist_page", page] => page.parse::<usize>().ok().map(CallbackAction::WatchlistPage),
+            ["scale", scale] => RatingScale::from_code(scale).map(CallbackAction::SetRatingScale),
+            ["lang", language] => Language::from_code(language).map(CallbackAction::SetLanguage),
+            ["dup", resolution, fingerprint] => {
+                let resolution = DuplicateResolution::from_code(resolution)?;
+                let fingerprint = u32::from_str_radix(fingerprint, 16).ok()?;
+                Some(CallbackAction::ResolveDuplicate(resolution, fingerprint))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn data(&self) -> String {
+        match self {
+            CallbackAction::Edit(kind, index, fingerprint) => format!("edit:{}:{}:{:x}", kind.code(), index, fingerprint),
+            CallbackAction::EditPage(kind, page) => format!("edit_page:{}:{}", kind.code(), page),
+            CallbackAction::Delete(kind, index, fingerprint) => format!("del:{}:{}:{:x}", kind.code(), index, fingerprint),
+            CallbackAction::ConfirmDelete(kind, index, fingerprint) => format!("del_yes:{}:{}:{:x}", kind.code(), index, fingerprint),
+            CallbackAction::CancelDelete => "del_no".to_string(),
+            CallbackAction::Page(kind, None, page) => format!("page:{}:{}", kind.code(), page),
+            CallbackAction::Page(kind, Some(shelf), page) => format!("page:{}:{}:{}", kind.code(), page, shelf.code()),
+            CallbackAction::Export(format) => format!("export:{}", format.code()),
+            CallbackAction::PickShelf(index, fingerprint) => format!("shelf:{}:{:x}", index, fingerprint),
+            CallbackAction::PickShelfPage(page) => format!("shelf_page:{}", page),
+            CallbackAction::MoveToShelf(index, fingerprint, shelf) => format!("shelve:{}:{:x}:{}", index, fingerprint, shelf.code()),
+            CallbackAction::Watched(index, fingerprint) => format!("watched:{}:{:x}", index, fingerprint),
+            CallbackAction::Unwatch(index, fingerprint) => format!("unwatch:{}:{:x}", index, fingerprint),
+            CallbackAction::WatchlistPage(page) => format!("watchlist_page:{}", page),
+            CallbackAction::SetRatingScale(scale) => format!("scale:{}", scale.code()),
+            CallbackAction::SetLanguage(language) => format!("lang:{}", language.code()),
+            CallbackAction::ResolveDuplicate(resolution, fingerprint) => format!("dup:{}:{:x}", resolution.code(), fingerprint),
+        }
+    }
+}
+
+/// FNV-1a hash of an entry label, stable across bot restarts and updates.
+pub fn fingerprint(label: &str) -> u32 {
+    label.bytes().fold(0x811c9dc5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
+
+pub fn button(text: &str, action: CallbackAction) -> InlineKeyboardButton {
+    InlineKeyboardButton::builder()
+        .text(text)
+        .callback_data(action.data())
+        .build()
+}
+
+/// Prev/Next buttons of a paginated message, `None` if there is a single page.
+pub fn navigation_row(language: Language, page: usize, pages: usize, action: impl Fn(usize) -> CallbackAction) -> Option<Vec<InlineKeyboardButton>> {
+    let mut navigation = vec![];
+    if page > 0 {
+        navigation.push(button(&t!(language, "list.previous"), action(page - 1)));
+    }
+    if page + 1 < pages {
+        navigation.push(button(&t!(language, "list.next"), action(page + 1)));
+    }
+    if navigation.is_empty() { None } else { Some(navigation) }
+}
+
+/// One button per entry on the given page, each on its own row, with Prev/Next buttons below.
+/// Telegram limits the size of inline keyboards, so large libraries can't get a button per entry at once.
+pub fn picker_keyboard(
+    language: Language,
+    labels: &[String],
+    page: usize,
+    action: impl Fn(usize) -> CallbackAction,
+    page_action: impl Fn(usize) -> CallbackAction,
+) -> InlineKeyboardMarkup {
+    let page_size = config().limits.page_size;
+    let pages = ((labels.len() + page_size - 1) / page_size).max(1);
+    let page = page.min(pages - 1);
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = labels.iter()
+        .enumerate()
+        .skip(page * page_size)
+        .take(page_size)
+        .map(|(index, label)| vec![button(label, action(index))])
+        .collect();
+    rows.extend(navigation_row(language, page, pages, page_action));
+    InlineKeyboardMarkup::builder()
+        .inline_keyboard(rows)
+        .build()
+}
+
+pub fn export_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::builder()
+        .inline_keyboard(vec![vec![
+            button("JSON", CallbackAction::Export(ExportFormat::Json)),
+            button("CSV", CallbackAction::Export(ExportFormat::Csv)),
+        ]])
+        .build()
+}
+
+pub fn confirm_delete_keyboard(language: Language, kind: MediaKind, index: usize, fingerprint: u32) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::builder()
+        .inline_keyboard(vec![vec![
+            button(&t!(language, "delete.yes"), CallbackAction::ConfirmDelete(kind, index, fingerprint)),
+            button(&t!(language, "delete.no"), CallbackAction::CancelDelete),
+        ]])
+        .build()
+}
+
+/// One button per shelf, for moving the book to it.
+pub fn shelves_keyboard(language: Language, index: usize, fingerprint: u32) -> InlineKeyboardMarkup {
+    let rows = ReadingStatus::ALL.iter()
+        .map(|shelf| vec![button(&shelf.name(language), CallbackAction::MoveToShelf(index, fingerprint, *shelf))])
+        .collect();
+    InlineKeyboardMarkup::builder()
+        .inline_keyboard(rows)
+        .build()
+}
+
+pub fn rating_scale_keyboard(language: Language) -> InlineKeyboardMarkup {
+    let rows = RatingScale::ALL.iter()
+        .map(|scale| vec![button(&scale.name(language), CallbackAction::SetRatingScale(*scale))])
+        .collect();
+    InlineKeyboardMarkup::builder()
+        .inline_keyboard(rows)
+        .build()
+}
+
+/// Each language is named in itself, so it can be found without reading the current one.
+pub fn language_keyboard() -> InlineKeyboardMarkup {
+    let rows = Language::ALL.iter()
+        .map(|language| vec![button(language.name(), CallbackAction::SetLanguage(*language))])
+        .collect();
+    InlineKeyboardMarkup::builder()
+        .inline_keyboard(rows)
+        .build()
+}
+
+pub fn duplicate_keyboard(language: Language, fingerprint: u32) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::builder()
+        .inline_keyboard(vec![vec![
+            button(&t!(language, "duplicate.keep"), CallbackAction::ResolveDuplicate(DuplicateResolution::KeepBoth, fingerprint)),
+            button(&t!(language, "duplicate.replace"), CallbackAction::ResolveDuplicate(DuplicateResolution::Replace, fingerprint)),
+            button(&t!(language, "duplicate.discard"), CallbackAction::ResolveDuplicate(DuplicateResolution::Discard, fingerprint)),
+        ]])
+        .build()
+}
@@ -0,0 +1,225 @@
+use crate::dialog_runtime::RuntimeKind;
+use crate::storage::StorageKind;
+
+use once_cell::sync::OnceCell;
+use telegram_api::Api;
+use uuid::Uuid;
+
+use std::env;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+const GOLEM_API_ROOT: &str = "https://release.api.golem.cloud/v1";
+const ADD_BOOK_TEMPLATE: &str = "d6e1ea5b-40aa-4f9c-92e2-9db58c02b45f";
+const ADD_MOVIE_TEMPLATE: &str = "9ac53019-2336-468c-916d-cd46c63bc24b";
+const ADD_QUOTE_TEMPLATE: &str = "c386feb3-fdfb-4e6a-a24c-cae39cd393f0";
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+/// Golem template of each dialog.
+#[derive(Debug, Clone)]
+pub struct Templates {
+    pub add_book: String,
+    pub add_movie: String,
+    pub add_quote: String,
+    // Not published yet, the golem runtime can't run the add series dialog until it's set
+    pub add_series: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Limits {
+    // Entries per page of the lists
+    pub page_size: usize,
+    // Results per media type of `/search`
+    pub search_results: usize,
+    // Size in bytes of a CSV file to import
+    pub import_size: u64,
+}
+
+/// Everything the bot reads from its environment, validated once by `load` when the bot starts.
+#[derive(Clone)]
+pub struct Config {
+    pub telegram_token: String,
+    pub telegram_api_url: String,
+    // Only needed by the golem dialog runtime
+    pub golem_token: Option<String>,
+    pub golem_api_root: String,
+    pub templates: Templates,
+    pub dialog_runtime: RuntimeKind,
+    pub storage: StorageKind,
+    // Seconds `get_updates` waits for an update before returning none
+    pub polling_timeout: u32,
+    // Only needed in webhook mode, Telegram sends it back with every update
+    pub webhook_secret: Option<String>,
+    // Telegram users allowed to see the configuration with `/config`
+    pub admin_ids: Vec<u64>,
+    pub limits: Limits,
+}
+
+// Secrets are left out, the configuration is shown to admins
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hidden = |secret: &Option<String>| secret.as_ref().map(|_| "<hidden>");
+        f.debug_struct("Config")
+            .field("telegram_api_url", &self.telegram_api_url)
+            .field("golem_token", &hidden(&self.golem_token))
+            .field("golem_api_root", &self.golem_api_root)
+            .field("templates", &self.templates)
+            .field("dialog_runtime", &self.dialog_runtime)
+            .field("storage", &self.storage)
+            .field("polling_timeout", &self.polling_timeout)
+            .field("webhook_secret", &hidden(&self.webhook_secret))
+            .field("admin_ids", &self.admin_ids)
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
+
+/// Collects every problem with the environment, so they can be fixed at once.
+struct Reader {
+    errors: Vec<String>,
+}
+
+impl Reader {
+    /// The parsed value of a variable, `None` if it is not set or malformed.
+    fn optional<T>(&mut self, name: &str, parse: impl Fn(&str) -> Result<T, String>) -> Option<T> {
+        let value = match env::var(name) {
+            Ok(value) if value.trim().is_empty() => return None,
+            Ok(value) => value,
+            Err(env::VarError::NotPresent) => return None,
+            Err(env::VarError::NotUnicode(_)) => {
+                self.errors.push(format!("{} is not valid UTF-8", name));
+                return None;
+            }
+        };
+        match parse(value.trim()) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.errors.push(format!("{} {}", name, err));
+                None
+            }
+        }
+    }
+
+    fn required<T: Default>(&mut self, name: &str, parse: impl Fn(&str) -> Result<T, String>) -> T {
+        let errors = self.errors.len();
+        let value = self.optional(name, parse);
+        if value.is_none() && self.errors.len() == errors {
+            self.errors.push(format!("{} is not set", name));
+        }
+        value.unwrap_or_default()
+    }
+
+    fn or<T>(&mut self, name: &str, default: T, parse: impl Fn(&str) -> Result<T, String>) -> T {
+        self.optional(name, parse).unwrap_or(default)
+    }
+}
+
+fn text(value: &str) -> Result<String, String> {
+    Ok(value.to_string())
+}
+
+fn telegram_token(token: &str) -> Result<String, String> {
+    match token.split_once(':') {
+        Some((bot_id, secret)) if !bot_id.is_empty() && bot_id.chars().all(|c| c.is_ascii_digit()) && !secret.is_empty() => {
+            Ok(token.to_string())
+        }
+        _ => Err("must look like 123456:ABC-DEF, as given by @BotFather".to_string()),
+    }
+}
+
+fn url(url: &str) -> Result<String, String> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(url.trim_end_matches('/').to_string())
+    } else {
+        Err("must be an http:// or https:// URL".to_string())
+    }
+}
+
+fn template(template: &str) -> Result<String, String> {
+    Uuid::parse_str(template)
+        .map(|_| template.to_string())
+        .map_err(|_| "must be a template UUID".to_string())
+}
+
+fn number_in<T: FromStr + PartialOrd + Display>(min: T, max: T) -> impl Fn(&str) -> Result<T, String> {
+    move |text| match text.parse::<T>() {
+        Ok(number) if number >= min && number <= max => Ok(number),
+        _ => Err(format!("must be a number from {} to {}", min, max)),
+    }
+}
+
+fn user_ids(text: &str) -> Result<Vec<u64>, String> {
+    text.split(',')
+        .map(|id| id.trim().parse::<u64>().map_err(|_| format!("must be Telegram user IDs separated by commas, not {}", id.trim())))
+        .collect()
+}
+
+/// Telegram accepts 1-256 characters `A-Z`, `a-z`, `0-9`, `_` and `-`.
+fn webhook_secret(secret: &str) -> Result<String, String> {
+    let valid_chars = secret.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if secret.len() > 256 || !valid_chars {
+        Err("must be 1-256 characters A-Z, a-z, 0-9, _ or -".to_string())
+    } else {
+        Ok(secret.to_string())
+    }
+}
+
+impl Config {
+    /// Reads the configuration from the environment variables, reporting every missing or malformed one.
+    pub fn from_env() -> Result<Config, String> {
+        let mut reader = Reader { errors: vec![] };
+        let config = Config {
+            telegram_token: reader.required("TELEGRAM_TOKEN", telegram_token),
+            telegram_api_url: reader.or("TELEGRAM_API_URL", TELEGRAM_API_URL.to_string(), url),
+            golem_token: reader.optional("GOLEM_TOKEN", text),
+            golem_api_root: reader.or("GOLEM_API_ROOT", GOLEM_API_ROOT.to_string(), url),
+            templates: Templates {
+                add_book: reader.or("ADD_BOOK_TEMPLATE", ADD_BOOK_TEMPLATE.to_string(), template),
+                add_movie: reader.or("ADD_MOVIE_TEMPLATE", ADD_MOVIE_TEMPLATE.to_string(), template),
+                add_quote: reader.or("ADD_QUOTE_TEMPLATE", ADD_QUOTE_TEMPLATE.to_string(), template),
+                add_series: reader.optional("ADD_SERIES_TEMPLATE", template),
+            },
+            dialog_runtime: reader.or("DIALOG_RUNTIME", RuntimeKind::default(), RuntimeKind::parse),
+            storage: reader.or("STORAGE", StorageKind::Memory, StorageKind::parse),
+            // The HTTP client gives up on a request after 500 seconds
+            polling_timeout: reader.or("POLLING_TIMEOUT", 10, number_in(0, 300)),
+            webhook_secret: reader.optional("WEBHOOK_SECRET", webhook_secret),
+            admin_ids: reader.or("ADMIN_IDS", vec![], user_ids),
+            limits: Limits {
+                page_size: reader.or("PAGE_SIZE", 10, number_in(1, 50)),
+                search_results: reader.or("SEARCH_RESULTS", 10, number_in(1, 50)),
+                // Telegram bots can download files up to 20 MB
+                import_size: reader.or("MAX_IMPORT_SIZE", 5_000_000, number_in(1, 20_000_000)),
+            },
+        };
+        if config.dialog_runtime == RuntimeKind::Golem && config.golem_token.is_none() {
+            reader.errors.push("GOLEM_TOKEN is not set, the golem dialog runtime needs it".to_string());
+        }
+        if reader.errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(format!("Invalid configuration: {}", reader.errors.join("; ")))
+        }
+    }
+
+    pub fn telegram_api(&self) -> Api {
+        Api::with_base_url(&self.telegram_api_url, &self.telegram_token)
+    }
+
+    pub fn is_admin(&self, user_id: u64) -> bool {
+        self.admin_ids.contains(&user_id)
+    }
+}
+
+/// Loads the configuration the first time it is called, later calls return the loaded configuration.
+pub fn load() -> Result<&'static Config, String> {
+    CONFIG.get_or_try_init(Config::from_env)
+}
+
+/// The configuration loaded when the bot started.
+pub fn config() -> &'static Config {
+    CONFIG.get().expect("The configuration is loaded before handling updates")
+}
@@ -0,0 +1,74 @@
+mod golem;
+#[cfg(feature = "local")]
+mod in_process;
+
+use crate::domain::DialogType;
+
+use dialog_engine::DialogSettings;
+use uuid::Uuid;
+
+pub use golem::GolemRuntime;
+#[cfg(feature = "local")]
+pub use in_process::InProcessRuntime;
+
+/// A call to a dialog, matching the functions of the dialog templates.
+pub enum DialogCall {
+    /// An update sent by the user, as JSON.
+    Step(String),
+    /// Prefills the dialog with an existing entry, as JSON.
+    Edit { chat_id: i64, entry: serde_json::Value },
+    /// Starts the add movie dialog at the rating step for a watchlist entry.
+    Watched { chat_id: i64, title: String, year: Option<u32> },
+}
+
+/// Where dialogs run. Each dialog is created for one user and disposed of once it completes.
+pub trait DialogRuntime {
+    /// Creates a dialog that asks for and shows ratings on the user's scale, in the user's language.
+    /// A Golem worker gets the settings in its environment, so they can't change for the dialog afterwards.
+    fn create(&mut self, dialog_type: DialogType, dialog_id: Uuid, settings: DialogSettings) -> Result<(), String>;
+
+    /// Calls the dialog and returns the result of the call as JSON, e.g. `{"ok": {"book": null}}`.
+    fn step(&mut self, dialog_type: DialogType, dialog_id: Uuid, call: DialogCall) -> Result<serde_json::Value, String>;
+
+    /// The state of the dialog's form as JSON.
+    fn inspect(&mut self, dialog_type: DialogType, dialog_id: Uuid) -> Result<String, String>;
+
+    fn dispose(&mut self, dialog_type: DialogType, dialog_id: Uuid);
+}
+
+/// Where dialogs run, set with the `DIALOG_RUNTIME` environment variable: `golem` or `in-process`.
+/// The default is `in-process` when the dialogs are built in, i.e. with the local feature, `golem` otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuntimeKind {
+    Golem,
+    InProcess,
+}
+
+impl Default for RuntimeKind {
+    fn default() -> RuntimeKind {
+        if cfg!(feature = "local") { RuntimeKind::InProcess } else { RuntimeKind::Golem }
+    }
+}
+
+impl RuntimeKind {
+    pub fn parse(text: &str) -> Result<RuntimeKind, String> {
+        match text {
+            "golem" => Ok(RuntimeKind::Golem),
+            #[cfg(feature = "local")]
+            "in-process" => Ok(RuntimeKind::InProcess),
+            #[cfg(not(feature = "local"))]
+            "in-process" => Err("in-process requires the local feature".to_string()),
+            _ => Err("must be golem or in-process".to_string()),
+        }
+    }
+}
+
+pub fn open(kind: RuntimeKind) -> Result<Box<dyn DialogRuntime>, String> {
+    match kind {
+        RuntimeKind::Golem => Ok(Box::new(GolemRuntime)),
+        #[cfg(feature = "local")]
+        RuntimeKind::InProcess => Ok(Box::new(InProcessRuntime::default())),
+        #[cfg(not(feature = "local"))]
+        RuntimeKind::InProcess => Err("The in-process dialog runtime requires the local feature".to_string()),
+    }
+}
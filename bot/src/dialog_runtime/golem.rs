@@ -0,0 +1,85 @@
+use super::{DialogCall, DialogRuntime};
+use crate::config::config;
+use crate::domain::{DialogType, ResultCaseInsensitive};
+use crate::workers::*;
+
+use dialog_engine::DialogSettings;
+use uuid::Uuid;
+
+const STEP_FUNCTION: &str = "golem%3Atemplate%2Fapi%2Fstep";
+const EDIT_FUNCTION: &str = "golem%3Atemplate%2Fapi%2Fedit";
+const WATCHED_FUNCTION: &str = "golem%3Atemplate%2Fapi%2Fwatched";
+const STATE_FUNCTION: &str = "golem%3Atemplate%2Fapi%2Fstate";
+
+fn template(dialog_type: DialogType) -> Result<&'static str, String> {
+    dialog_type.template().ok_or("No Golem template is configured for the dialog".to_string())
+}
+
+/// Runs each dialog in its own Golem worker, created from the dialog's template through the Golem REST API.
+pub struct GolemRuntime;
+
+impl GolemRuntime {
+    fn invoke(&self, dialog_type: DialogType, dialog_id: Uuid, function: &str, params: Vec<serde_json::Value>) -> Result<serde_json::Value, String> {
+        let template = template(dialog_type)?;
+        let invocation_key = get_invocation_key(dialog_id, template)
+            .map_err(|err| format!("Error getting invocation key: {}", err))?;
+        let response_json = invoke_function(template, dialog_id, invocation_key, function, params)
+            .map_err(|err| format!("Dialog step failed: {}", err))?;
+        println!("{:?}", response_json);
+
+        // Deserialize JSON and handle any error
+        let parsed_response = serde_json::from_value::<FunctionResult<serde_json::Value>>(response_json)
+            .map_err(|err| format!("JSON deserialization failed: {}", err))?;
+
+        // Handle the Result inside the parsed_response
+        parsed_response
+            .result
+            .into_iter()
+            .next()
+            .ok_or("No result found in dialog step".to_string())
+    }
+}
+
+impl DialogRuntime for GolemRuntime {
+    fn create(&mut self, dialog_type: DialogType, dialog_id: Uuid, settings: DialogSettings) -> Result<(), String> {
+        // Each worker runs a single dialog, so its environment can carry the settings of the dialog's user
+        let mut env = vec![
+            vec!["TELEGRAM_API_URL", config().telegram_api_url.as_str()],
+            vec!["RATING_SCALE", settings.rating_scale.code()],
+            vec!["LANGUAGE", settings.language.code()],
+        ];
+        if let Some(token) = &settings.telegram_token {
+            env.push(vec!["TELEGRAM_TOKEN", token.as_str()]);
+        }
+        create_worker(dialog_id, template(dialog_type)?, env)
+            .map_err(|err| format!("Failed to create worker: {}", err))
+    }
+
+    fn step(&mut self, dialog_type: DialogType, dialog_id: Uuid, call: DialogCall) -> Result<serde_json::Value, String> {
+        let (function, params) = match call {
+            DialogCall::Step(update) => (STEP_FUNCTION, vec![serde_json::json!(update)]),
+            DialogCall::Edit { chat_id, entry } => (EDIT_FUNCTION, vec![serde_json::json!(chat_id), entry]),
+            DialogCall::Watched { chat_id, title, year } => (
+                WATCHED_FUNCTION,
+                vec![serde_json::json!(chat_id), serde_json::json!(title), serde_json::json!(year)],
+            ),
+        };
+        self.invoke(dialog_type, dialog_id, function, params)
+    }
+
+    fn inspect(&mut self, dialog_type: DialogType, dialog_id: Uuid) -> Result<String, String> {
+        let result = self.invoke(dialog_type, dialog_id, STATE_FUNCTION, vec![])?;
+        match serde_json::from_value::<ResultCaseInsensitive<String, String>>(result) {
+            Ok(ResultCaseInsensitive::Ok(state)) => Ok(state),
+            Ok(ResultCaseInsensitive::Err(err)) => Err(format!("Error inspecting dialog: {}", err)),
+            Err(err) => Err(format!("JSON deserialization failed: {}", err)),
+        }
+    }
+
+    fn dispose(&mut self, dialog_type: DialogType, dialog_id: Uuid) {
+        // Without a template no worker was created
+        if let Some(template) = dialog_type.template() {
+            delete_worker(template, dialog_id)
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use super::{DialogCall, DialogRuntime};
+use crate::domain::DialogType;
+
+use dialog_engine::{DialogSettings, FormState};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use std::collections::HashMap;
+
+/// Calls the dialog crates directly, keeping the form state of each dialog in memory.
+/// Results are the same JSON a Golem worker returns, so the bot handles them the same way.
+#[derive(Default)]
+pub struct InProcessRuntime {
+    dialogs: HashMap<Uuid, FormState>,
+}
+
+impl InProcessRuntime {
+    fn dialog(&mut self, dialog_id: Uuid) -> Result<&mut FormState, String> {
+        self.dialogs.get_mut(&dialog_id).ok_or(format!("Unknown dialog {}", dialog_id))
+    }
+}
+
+fn param<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, String> {
+    serde_json::from_value(value).map_err(|err| format!("Invalid dialog parameter: {}", err))
+}
+
+// Serialized as `{"Ok": …}` or `{"Err": …}`, which `ResultCaseInsensitive` accepts like Golem's `ok` and `err`
+fn to_json<T: Serialize>(result: Result<T, String>) -> Result<serde_json::Value, String> {
+    serde_json::to_value(result).map_err(|err| format!("JSON serialization failed: {}", err))
+}
+
+impl DialogRuntime for InProcessRuntime {
+    fn create(&mut self, _dialog_type: DialogType, dialog_id: Uuid, settings: DialogSettings) -> Result<(), String> {
+        self.dialogs.insert(dialog_id, FormState::new(settings));
+        println!("Created in-process dialog with ID {}", dialog_id);
+        Ok(())
+    }
+
+    fn step(&mut self, dialog_type: DialogType, dialog_id: Uuid, call: DialogCall) -> Result<serde_json::Value, String> {
+        let state = self.dialog(dialog_id)?;
+        match (dialog_type, call) {
+            (DialogType::AddBook, DialogCall::Step(update)) => to_json(add_book_dialog::step(state, &update)),
+            (DialogType::AddBook, DialogCall::Edit { chat_id, entry }) => to_json(add_book_dialog::edit(state, chat_id, param(entry)?)),
+            (DialogType::AddMovie, DialogCall::Step(update)) => to_json(add_movie_dialog::step(state, &update)),
+            (DialogType::AddMovie, DialogCall::Edit { chat_id, entry }) => to_json(add_movie_dialog::edit(state, chat_id, param(entry)?)),
+            (DialogType::AddMovie, DialogCall::Watched { chat_id, title, year }) => to_json(add_movie_dialog::watched(state, chat_id, title, year)),
+            (DialogType::AddQuote, DialogCall::Step(update)) => to_json(add_quote_dialog::step(state, &update)),
+            (DialogType::AddQuote, DialogCall::Edit { chat_id, entry }) => to_json(add_quote_dialog::edit(state, chat_id, param(entry)?)),
+            (DialogType::AddSeries, DialogCall::Step(update)) => to_json(add_series_dialog::step(state, &update)),
+            (DialogType::AddSeries, DialogCall::Edit { chat_id, entry }) => to_json(add_series_dialog::edit(state, chat_id, param(entry)?)),
+            (_, DialogCall::Watched { .. }) => Err("Only the add movie dialog starts from the watchlist".to_string()),
+        }
+    }
+
+    fn inspect(&mut self, _dialog_type: DialogType, dialog_id: Uuid) -> Result<String, String> {
+        let state = self.dialog(dialog_id)?;
+        serde_json::to_string(state).map_err(|err| format!("JSON serialization failed: {}", err))
+    }
+
+    fn dispose(&mut self, _dialog_type: DialogType, dialog_id: Uuid) {
+        self.dialogs.remove(&dialog_id);
+    }
+}
@@ -1,61 +1,139 @@
-use crate::domain::{Dialog, DialogType, State};
-use crate::env::TELEGRAM_TOKEN;
-use crate::workers::*;
+use crate::config::config;
+use crate::dialog_runtime::DialogCall;
+use crate::domain::{Dialog, DialogType, ResultCaseInsensitive, State, WatchlistItem};
 
+use dialog_engine::DialogSettings;
 use frankenstein::Update;
 use uuid::Uuid;
 
 use std::fmt::Debug;
 use serde::de::DeserializeOwned;
 
-pub fn create_dialog<F>(
+fn start_dialog(
     state: &mut State,
     user_id: u64,
     dialog_type: DialogType,
-    update: &Update,
-    step: F,
-) -> Result<(), String>
-    where F: Fn(&mut State, u64, Uuid, String, &Update) -> Result<(), String> {
+    edit_index: Option<usize>,
+    watchlist_index: Option<usize>,
+) -> Result<Uuid, String> {
     let dialog_id = Uuid::new_v4();
-    let env = vec!("TELEGRAM_TOKEN", TELEGRAM_TOKEN.as_str());
-    if let Err(err) = create_worker(dialog_id, dialog_type.template(), vec!(env)) {
-        return Err(format!("Failed to create worker: {}", err));
-    }
+    let settings = DialogSettings {
+        language: state.language(user_id),
+        rating_scale: state.rating_scale(user_id),
+        telegram_token: Some(config().telegram_token.clone()),
+    };
+    state.dialog_runtime.create(dialog_type, dialog_id, settings)?;
 
     state.dialogs.insert(user_id, Dialog {
         dialog_type,
         dialog_id,
+        edit_index,
+        watchlist_index,
     });
+    Ok(dialog_id)
+}
 
-    let invocation_key = get_invocation_key(dialog_id, dialog_type.template())
-        .map_err(|err| format!("Error getting invocation key: {}", err))?;
+pub fn create_dialog<F>(
+    state: &mut State,
+    user_id: u64,
+    dialog_type: DialogType,
+    update: &Update,
+    step: F,
+) -> Result<(), String>
+    where F: Fn(&mut State, u64, Uuid, &Update) -> Result<(), String> {
+    let dialog_id = start_dialog(state, user_id, dialog_type, None, None)?;
 
-    step(state, user_id, dialog_id, invocation_key, update)
+    step(state, user_id, dialog_id, update)
         .map_err(|err| format!("Error in dialog step: {}", err))
 }
 
-pub fn dialog_step<T: DeserializeOwned + Debug>(template: &str, dialog_id: Uuid, invocation_key: String, update: &Update) -> Result<T, String> {
+/// Starts a dialog prefilled with an existing entry, which replaces the entry at `index` on completion.
+pub fn create_edit_dialog(
+    state: &mut State,
+    user_id: u64,
+    dialog_type: DialogType,
+    index: usize,
+    chat_id: i64,
+    entry: serde_json::Value,
+) -> Result<(), String> {
+    let dialog_id = start_dialog(state, user_id, dialog_type, Some(index), None)?;
+
+    let result = call_dialog::<ResultCaseInsensitive<serde_json::Value, String>>(
+        state, dialog_type, dialog_id, DialogCall::Edit { chat_id, entry },
+    )?;
+    match result {
+        ResultCaseInsensitive::Ok(_) => Ok(()),
+        ResultCaseInsensitive::Err(err) => Err(format!("Error in edit dialog: {}", err)),
+    }
+}
+
+/// Starts the add movie dialog at the rating step for the watchlist entry at `index`,
+/// which is removed from the watchlist on completion.
+pub fn create_watched_dialog(
+    state: &mut State,
+    user_id: u64,
+    index: usize,
+    chat_id: i64,
+    item: &WatchlistItem,
+) -> Result<(), String> {
+    let dialog_type = DialogType::AddMovie;
+    let dialog_id = start_dialog(state, user_id, dialog_type, None, Some(index))?;
+
+    let call = DialogCall::Watched { chat_id, title: item.title.clone(), year: item.year };
+    let result = call_dialog::<ResultCaseInsensitive<serde_json::Value, String>>(state, dialog_type, dialog_id, call)?;
+    match result {
+        ResultCaseInsensitive::Ok(_) => Ok(()),
+        ResultCaseInsensitive::Err(err) => Err(format!("Error in watched dialog: {}", err)),
+    }
+}
+
+pub fn dialog_step<T: DeserializeOwned + Debug>(state: &mut State, dialog_type: DialogType, dialog_id: Uuid, update: &Update) -> Result<T, String> {
     let update_param = serde_json::to_string(update)
         .map_err(|err| format!("Update serialization error: {}", err))?;
 
-    let response_json = invoke_function(template, dialog_id, invocation_key, "golem%3Atemplate%2Fapi%2Fstep", update_param)
-        .map_err(|err| format!("Dialog step failed: {}", err))?;
-    println!("{:?}", response_json);
+    call_dialog(state, dialog_type, dialog_id, DialogCall::Step(update_param))
+}
+
+fn call_dialog<T: DeserializeOwned + Debug>(
+    state: &mut State,
+    dialog_type: DialogType,
+    dialog_id: Uuid,
+    call: DialogCall,
+) -> Result<T, String> {
+    let result = state.dialog_runtime.step(dialog_type, dialog_id, call)?;
 
     // Deserialize JSON and handle any error
-    let parsed_response = serde_json::from_value::<FunctionResult<T>>(response_json)
-        .map_err(|err| format!("JSON deserialization failed: {}", err))?;
+    serde_json::from_value::<T>(result)
+        .map_err(|err| format!("JSON deserialization failed: {}", err))
+}
 
-    // Handle the Result inside the parsed_response
-    let first_result = parsed_response
-        .result
-        .into_iter()
-        .next()
-        .ok_or("No result found in dialog step".to_string());
-    first_result
+/// Logs the form state of a dialog, to see where a failing dialog is stuck.
+pub fn log_dialog_state(state: &mut State, dialog: &Dialog) {
+    match state.dialog_runtime.inspect(dialog.dialog_type, dialog.dialog_id) {
+        Ok(form) => println!("State of dialog {}: {}", dialog.dialog_id, form),
+        Err(err) => println!("Error inspecting dialog {}: {}", dialog.dialog_id, err),
+    }
+}
+
+/// Stores a dialog result: replaces the edited entry or appends a new one.
+/// Returns `false` if the edited entry no longer exists, rather than adding it again.
+pub fn save_entry<T>(entries: &mut Vec<T>, entry: T, edit_index: Option<usize>) -> bool {
+    match edit_index {
+        Some(index) => match entries.get_mut(index) {
+            Some(existing) => {
+                *existing = entry;
+                true
+            }
+            None => false,
+        },
+        None => {
+            entries.push(entry);
+            true
+        }
+    }
 }
 
-pub fn dispose_dialog(state: &mut State, user_id: u64, template: &str, dialog_id: Uuid) {
+pub fn dispose_dialog(state: &mut State, user_id: u64, dialog_type: DialogType, dialog_id: Uuid) {
     state.dialogs.remove(&user_id);
-    delete_worker(template, dialog_id)
+    state.dialog_runtime.dispose(dialog_type, dialog_id)
 }
@@ -1,5 +1,10 @@
+use crate::config::{Config, config};
+use crate::dialog_runtime::{self, DialogRuntime, GolemRuntime, RuntimeKind};
+use crate::storage::{self, MemoryStorage, Storage, UserData};
+
+use dialog_engine::{Language, RatingScale, t};
 use once_cell::sync::Lazy;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use std::collections::HashMap;
@@ -8,46 +13,346 @@ use std::collections::HashMap;
 pub enum DialogType {
     AddBook,
     AddMovie,
-    AddQuote
+    AddQuote,
+    AddSeries,
 }
 
 impl DialogType {
-    pub fn template(&self) -> &str {
+    /// The Golem template of the dialog, `None` if it isn't configured.
+    pub fn template(&self) -> Option<&'static str> {
+        let templates = &config().templates;
         match self {
-            DialogType::AddBook => "d6e1ea5b-40aa-4f9c-92e2-9db58c02b45f",
-            DialogType::AddMovie => "9ac53019-2336-468c-916d-cd46c63bc24b",
-            DialogType::AddQuote => "c386feb3-fdfb-4e6a-a24c-cae39cd393f0",
+            DialogType::AddBook => Some(templates.add_book.as_str()),
+            DialogType::AddMovie => Some(templates.add_movie.as_str()),
+            DialogType::AddQuote => Some(templates.add_quote.as_str()),
+            DialogType::AddSeries => templates.add_series.as_deref(),
         }
     }
+
+    /// Whether the configured runtime can run the dialog, in Golem it needs a template.
+    pub fn available(&self) -> bool {
+        config().dialog_runtime != RuntimeKind::Golem || self.template().is_some()
+    }
 }
 
 #[derive(Clone)]
 pub struct Dialog {
     pub dialog_type: DialogType,
     pub dialog_id: Uuid,
+    // Index of the entry being replaced when the dialog edits an existing item
+    pub edit_index: Option<usize>,
+    // Index of the watchlist entry to remove once the movie dialog saves it as watched
+    pub watchlist_index: Option<usize>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MediaKind {
+    Book,
+    Movie,
+    Quote,
+    Series,
+}
+
+impl MediaKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            MediaKind::Book => "book",
+            MediaKind::Movie => "movie",
+            MediaKind::Quote => "quote",
+            MediaKind::Series => "series",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<MediaKind> {
+        match code {
+            "book" => Some(MediaKind::Book),
+            "movie" => Some(MediaKind::Movie),
+            "quote" => Some(MediaKind::Quote),
+            "series" => Some(MediaKind::Series),
+            _ => None,
+        }
+    }
+
+    pub fn dialog_type(&self) -> DialogType {
+        match self {
+            MediaKind::Book => DialogType::AddBook,
+            MediaKind::Movie => DialogType::AddMovie,
+            MediaKind::Quote => DialogType::AddQuote,
+            MediaKind::Series => DialogType::AddSeries,
+        }
+    }
+}
+
+/// The shelf a book is on. Books added before shelves existed are finished.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReadingStatus {
+    WantToRead,
+    Reading,
+    #[default]
+    Finished,
+    Abandoned,
+}
+
+impl ReadingStatus {
+    pub const ALL: [ReadingStatus; 4] = [
+        ReadingStatus::WantToRead,
+        ReadingStatus::Reading,
+        ReadingStatus::Finished,
+        ReadingStatus::Abandoned,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ReadingStatus::WantToRead => "want",
+            ReadingStatus::Reading => "reading",
+            ReadingStatus::Finished => "finished",
+            ReadingStatus::Abandoned => "abandoned",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<ReadingStatus> {
+        ReadingStatus::ALL.into_iter().find(|status| status.code() == code)
+    }
+
+    pub fn name(&self, language: Language) -> String {
+        t!(language, &format!("shelf.{}", self.code()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Book {
     pub title: String,
     pub author: String,
-    pub rating: u32,
+    #[serde(default)]
+    pub status: ReadingStatus,
+    // Score out of 100, only finished books are rated
+    pub rating: Option<u32>,
+    // Date read, YYYY-MM-DD
+    #[serde(default)]
+    pub date: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Quote {
     pub text: String,
     pub title: String,
-    pub author: String,
+    pub author: Option<String>,
+    // Date saved, YYYY-MM-DD
+    #[serde(default)]
+    pub date: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Movie {
     pub title: String,
-    pub year: u32,
+    pub year: Option<u32>,
+    // Score out of 100
+    pub rating: u32,
+    // Date watched, YYYY-MM-DD
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Series {
+    pub title: String,
+    pub seasons: u32,
+    // Score out of 100
     pub rating: u32,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub rating_scale: RatingScale,
+    // Chosen with /language, overrides the language of the Telegram app
+    pub language: Option<Language>,
+    // From `User::language_code` of the latest update
+    pub telegram_language: Option<Language>,
+}
+
+/// A movie to watch, not rated yet.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WatchlistItem {
+    pub title: String,
+    pub year: Option<u32>,
+}
+
+impl Book {
+    pub fn label(&self) -> String {
+        format!("{} by {}", self.title, self.author)
+    }
+}
+
+impl Movie {
+    pub fn label(&self) -> String {
+        match self.year {
+            Some(year) => format!("{} ({})", self.title, year),
+            None => self.title.clone(),
+        }
+    }
+}
+
+impl WatchlistItem {
+    pub fn label(&self) -> String {
+        match self.year {
+            Some(year) => format!("{} ({})", self.title, year),
+            None => self.title.clone(),
+        }
+    }
+}
+
+impl Series {
+    pub fn label(&self, language: Language) -> String {
+        format!("{} ({})", self.title, language.count("count.season", self.seasons as usize))
+    }
+}
+
+impl Quote {
+    pub fn label(&self) -> String {
+        let text: String = self.text.chars().take(30).collect();
+        if text.len() < self.text.len() {
+            format!("\"{}…\" from {}", text, self.title)
+        } else {
+            format!("\"{}\" from {}", text, self.title)
+        }
+    }
+}
+
+/// A dialog result of any media type.
+#[derive(Debug, Clone)]
+pub enum Entry {
+    Book(Book),
+    Movie(Movie),
+    Quote(Quote),
+    Series(Series),
+}
+
+impl Entry {
+    pub fn kind(&self) -> MediaKind {
+        match self {
+            Entry::Book(_) => MediaKind::Book,
+            Entry::Movie(_) => MediaKind::Movie,
+            Entry::Quote(_) => MediaKind::Quote,
+            Entry::Series(_) => MediaKind::Series,
+        }
+    }
+
+    pub fn label(&self, language: Language) -> String {
+        match self {
+            Entry::Book(book) => book.label(),
+            Entry::Movie(movie) => movie.label(),
+            Entry::Quote(quote) => quote.label(),
+            Entry::Series(series) => series.label(language),
+        }
+    }
+}
+
+/// A new entry waiting for the user to decide what to do about the existing entry at `index`.
+#[derive(Debug, Clone)]
+pub struct PendingDuplicate {
+    pub entry: Entry,
+    pub index: usize,
+    // The watchlist title a movie was rated from, which leaves the watchlist only if the movie is kept
+    pub watchlist_index: Option<usize>,
+}
+
+/// The whole library of a user, as exported to JSON.
+#[derive(Serialize)]
+pub struct Library<'a> {
+    pub books: &'a [Book],
+    pub movies: &'a [Movie],
+    pub quotes: &'a [Quote],
+    pub series: &'a [Series],
+}
+
+impl Library<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty() && self.movies.is_empty() && self.quotes.is_empty() && self.series.is_empty()
+    }
+}
+
+/// A flat row covering every media type, as exported to CSV.
+#[derive(Serialize)]
+pub struct LibraryRecord<'a> {
+    #[serde(rename = "type")]
+    pub kind: &'a str,
+    pub title: &'a str,
+    pub author: Option<&'a str>,
+    pub status: Option<&'a str>,
+    pub year: Option<u32>,
+    pub seasons: Option<u32>,
+    // Score out of 100, as stored
+    pub rating: Option<u32>,
+    pub text: Option<&'a str>,
+    pub date: Option<&'a str>,
+}
+
+impl<'a> From<&'a Book> for LibraryRecord<'a> {
+    fn from(book: &'a Book) -> Self {
+        LibraryRecord {
+            kind: MediaKind::Book.code(),
+            title: &book.title,
+            author: Some(&book.author),
+            status: Some(book.status.code()),
+            year: None,
+            seasons: None,
+            rating: book.rating,
+            text: None,
+            date: book.date.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a Movie> for LibraryRecord<'a> {
+    fn from(movie: &'a Movie) -> Self {
+        LibraryRecord {
+            kind: MediaKind::Movie.code(),
+            title: &movie.title,
+            author: None,
+            status: None,
+            year: movie.year,
+            seasons: None,
+            rating: Some(movie.rating),
+            text: None,
+            date: movie.date.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a Quote> for LibraryRecord<'a> {
+    fn from(quote: &'a Quote) -> Self {
+        LibraryRecord {
+            kind: MediaKind::Quote.code(),
+            title: &quote.title,
+            author: quote.author.as_deref(),
+            status: None,
+            year: None,
+            seasons: None,
+            rating: None,
+            text: Some(&quote.text),
+            date: quote.date.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a Series> for LibraryRecord<'a> {
+    fn from(series: &'a Series) -> Self {
+        LibraryRecord {
+            kind: MediaKind::Series.code(),
+            title: &series.title,
+            author: None,
+            status: None,
+            year: None,
+            seasons: Some(series.seasons),
+            rating: Some(series.rating),
+            text: None,
+            date: None,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub enum ResultCaseInsensitive<T, E> {
     #[serde(alias = "Ok", alias = "ok")]
@@ -60,23 +365,177 @@ pub enum ResultCaseInsensitive<T, E> {
 /// uses. Golem will take care to persist all application state,
 /// whether that state is local to a function being executed or
 /// global across the entire program.
+/// The library is also kept in `storage`, so it can outlive the worker.
+/// Dialogs run in `dialog_runtime`, only their IDs are kept here.
 pub struct State {
     pub dialogs: Lazy<HashMap<u64, Dialog>>,
     pub books: Lazy<HashMap<u64, Vec<Book>>>,
     pub movies: Lazy<HashMap<u64, Vec<Movie>>>,
     pub quotes: Lazy<HashMap<u64, Vec<Quote>>>,
+    pub series: Lazy<HashMap<u64, Vec<Series>>>,
+    pub watchlist: Lazy<HashMap<u64, Vec<WatchlistItem>>>,
+    pub settings: Lazy<HashMap<u64, Settings>>,
+    pub duplicates: Lazy<HashMap<u64, PendingDuplicate>>,
+    pub dialog_runtime: Lazy<Box<dyn DialogRuntime>>,
+    pub storage: Lazy<Box<dyn Storage>>,
+    // JSON of the data last loaded or saved per user, so an update that changed nothing isn't saved again
+    pub saved: Lazy<HashMap<u64, String>>,
+    pub started: bool,
+}
+
+impl State {
+    /// A state without any users, `STATE` until the bot starts.
+    pub const fn empty() -> State {
+        State {
+            dialogs: Lazy::new(|| HashMap::new()),
+            books: Lazy::new(|| HashMap::new()),
+            movies: Lazy::new(|| HashMap::new()),
+            quotes: Lazy::new(|| HashMap::new()),
+            series: Lazy::new(|| HashMap::new()),
+            watchlist: Lazy::new(|| HashMap::new()),
+            settings: Lazy::new(|| HashMap::new()),
+            duplicates: Lazy::new(|| HashMap::new()),
+            // Replaced by the configured ones in `State::start`
+            dialog_runtime: Lazy::new(|| Box::new(GolemRuntime) as Box<dyn DialogRuntime>),
+            storage: Lazy::new(|| Box::new(MemoryStorage) as Box<dyn Storage>),
+            saved: Lazy::new(|| HashMap::new()),
+            started: false,
+        }
+    }
+
+    /// Opens the configured dialog runtime and storage and loads the library, the first time the bot is called.
+    pub fn start(&mut self, config: &Config) -> Result<(), String> {
+        if self.started {
+            return Ok(());
+        }
+        *self.dialog_runtime = dialog_runtime::open(config.dialog_runtime)?;
+        *self.storage = storage::open(&config.storage)?;
+        self.load()?;
+        self.started = true;
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<(), String> {
+        // Going on without the stored data would overwrite it with empty libraries
+        let users = self.storage.load().map_err(|err| format!("Error loading the library: {}", err))?;
+        for (user_id, data) in users {
+            if let Ok(json) = serde_json::to_string(&data) {
+                self.saved.insert(user_id, json);
+            }
+            self.books.insert(user_id, data.books);
+            self.movies.insert(user_id, data.movies);
+            self.quotes.insert(user_id, data.quotes);
+            self.series.insert(user_id, data.series);
+            self.watchlist.insert(user_id, data.watchlist);
+            self.settings.insert(user_id, data.settings);
+        }
+        Ok(())
+    }
+
+    pub fn user_data(&self, user_id: u64) -> UserData {
+        UserData {
+            books: self.books.get(&user_id).cloned().unwrap_or_default(),
+            movies: self.movies.get(&user_id).cloned().unwrap_or_default(),
+            quotes: self.quotes.get(&user_id).cloned().unwrap_or_default(),
+            series: self.series.get(&user_id).cloned().unwrap_or_default(),
+            watchlist: self.watchlist.get(&user_id).cloned().unwrap_or_default(),
+            settings: self.settings.get(&user_id).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Writes the user's data to the storage if it changed since it was last saved.
+    pub fn save(&mut self, user_id: u64) {
+        let data = self.user_data(user_id);
+        let json = match serde_json::to_string(&data) {
+            Ok(json) => json,
+            Err(err) => {
+                println!("JSON serialization failed: {}", err);
+                return;
+            }
+        };
+        if self.saved.get(&user_id) == Some(&json) {
+            return;
+        }
+        match self.storage.save(user_id, &data) {
+            Ok(()) => {
+                self.saved.insert(user_id, json);
+            }
+            Err(err) => println!("Error saving the library of user {}: {}", user_id, err),
+        }
+    }
+
+    pub fn rating_scale(&self, user_id: u64) -> RatingScale {
+        self.settings.get(&user_id).map(|settings| settings.rating_scale).unwrap_or_default()
+    }
+
+    pub fn language(&self, user_id: u64) -> Language {
+        self.settings.get(&user_id)
+            .and_then(|settings| settings.language.or(settings.telegram_language))
+            .unwrap_or_default()
+    }
+
+    /// Remembers the language of the user's Telegram app, the messages follow it unless a language was chosen.
+    pub fn set_telegram_language(&mut self, user_id: u64, language_code: Option<&str>) {
+        let language = language_code.and_then(Language::from_code);
+        if language != self.settings.get(&user_id).and_then(|settings| settings.telegram_language) {
+            self.settings.entry(user_id).or_default().telegram_language = language;
+        }
+    }
+
+    /// Button labels of the user's entries of the given kind, in storage order.
+    pub fn labels(&self, user_id: u64, kind: MediaKind) -> Vec<String> {
+        match kind {
+            MediaKind::Book => self.books.get(&user_id).map(|books| books.iter().map(Book::label).collect()),
+            MediaKind::Movie => self.movies.get(&user_id).map(|movies| movies.iter().map(Movie::label).collect()),
+            MediaKind::Quote => self.quotes.get(&user_id).map(|quotes| quotes.iter().map(Quote::label).collect()),
+            MediaKind::Series => {
+                let language = self.language(user_id);
+                self.series.get(&user_id).map(|series| series.iter().map(|series| series.label(language)).collect())
+            }
+        }.unwrap_or_default()
+    }
+
+    pub fn library(&self, user_id: u64) -> Library {
+        Library {
+            books: self.books.get(&user_id).map_or(&[], |books| books.as_slice()),
+            movies: self.movies.get(&user_id).map_or(&[], |movies| movies.as_slice()),
+            quotes: self.quotes.get(&user_id).map_or(&[], |quotes| quotes.as_slice()),
+            series: self.series.get(&user_id).map_or(&[], |series| series.as_slice()),
+        }
+    }
+
+    pub fn label(&self, user_id: u64, kind: MediaKind, index: usize) -> Option<String> {
+        self.labels(user_id, kind).into_iter().nth(index)
+    }
+
+    /// Removes the entry and returns its label.
+    pub fn remove_entry(&mut self, user_id: u64, kind: MediaKind, index: usize) -> Option<String> {
+        let label = self.label(user_id, kind, index)?;
+        match kind {
+            MediaKind::Book => self.books.get_mut(&user_id).map(|books| books.remove(index)).map(|_| ()),
+            MediaKind::Movie => self.movies.get_mut(&user_id).map(|movies| movies.remove(index)).map(|_| ()),
+            MediaKind::Quote => self.quotes.get_mut(&user_id).map(|quotes| quotes.remove(index)).map(|_| ()),
+            MediaKind::Series => self.series.get_mut(&user_id).map(|series| series.remove(index)).map(|_| ()),
+        }?;
+        Some(label)
+    }
+
+    pub fn entry_json(&self, user_id: u64, kind: MediaKind, index: usize) -> Option<serde_json::Value> {
+        let entry = match kind {
+            MediaKind::Book => self.books.get(&user_id).and_then(|books| books.get(index)).map(serde_json::to_value),
+            MediaKind::Movie => self.movies.get(&user_id).and_then(|movies| movies.get(index)).map(serde_json::to_value),
+            MediaKind::Quote => self.quotes.get(&user_id).and_then(|quotes| quotes.get(index)).map(serde_json::to_value),
+            MediaKind::Series => self.series.get(&user_id).and_then(|series| series.get(index)).map(serde_json::to_value),
+        };
+        entry.and_then(|json| json.ok())
+    }
 }
 
 /// This holds the state of our application.
 /// It is a global variable, which Rust doesn't like, so
 /// we use `with_state` to access or update the global variable, so we
 /// can avoid `unsafe` noise.
-pub static mut STATE: State = State {
-    dialogs: Lazy::new(|| HashMap::new()),
-    books: Lazy::new(|| HashMap::new()),
-    movies: Lazy::new(|| HashMap::new()),
-    quotes: Lazy::new(|| HashMap::new()),
-};
+pub static mut STATE: State = State::empty();
 
 pub fn with_state<T>(f: impl FnOnce(&mut State) -> T) -> T {
     unsafe { f(&mut STATE) }
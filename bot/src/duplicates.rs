@@ -0,0 +1,164 @@
+use crate::callbacks::{duplicate_keyboard, fingerprint};
+use crate::dialogs::save_entry;
+use crate::domain::{Entry, PendingDuplicate, State};
+use crate::config::config;
+use crate::search::normalize;
+
+use dialog_engine::t;
+use frankenstein::{Update, UpdateContent};
+use telegram_api::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DuplicateResolution {
+    KeepBoth,
+    Replace,
+    Discard,
+}
+
+impl DuplicateResolution {
+    pub fn code(&self) -> &'static str {
+        match self {
+            DuplicateResolution::KeepBoth => "keep",
+            DuplicateResolution::Replace => "replace",
+            DuplicateResolution::Discard => "discard",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<DuplicateResolution> {
+        match code {
+            "keep" => Some(DuplicateResolution::KeepBoth),
+            "replace" => Some(DuplicateResolution::Replace),
+            "discard" => Some(DuplicateResolution::Discard),
+            _ => None,
+        }
+    }
+}
+
+/// Ignores case, diacritics and runs of whitespace, so "The  Hobbit " matches "the hobbit".
+fn key(text: &str) -> String {
+    normalize(text).split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Index of an existing entry the new one is likely a copy of.
+fn find_duplicate(state: &State, user_id: u64, entry: &Entry) -> Option<usize> {
+    match entry {
+        Entry::Book(book) => state.books.get(&user_id)?.iter()
+            .position(|other| key(&other.title) == key(&book.title) && key(&other.author) == key(&book.author)),
+        // Remakes share a title, so years only have to match when both are known
+        Entry::Movie(movie) => state.movies.get(&user_id)?.iter()
+            .position(|other| key(&other.title) == key(&movie.title)
+                && (other.year.is_none() || movie.year.is_none() || other.year == movie.year)),
+        Entry::Quote(quote) => state.quotes.get(&user_id)?.iter()
+            .position(|other| key(&other.text) == key(&quote.text) && key(&other.title) == key(&quote.title)),
+        Entry::Series(series) => state.series.get(&user_id)?.iter()
+            .position(|other| key(&other.title) == key(&series.title)),
+    }
+}
+
+/// Replaces the entry at `index` or appends a new one, `false` if there is no entry at `index`.
+fn store(state: &mut State, user_id: u64, entry: Entry, index: Option<usize>) -> bool {
+    match entry {
+        Entry::Book(book) => save_entry(state.books.entry(user_id).or_insert(vec![]), book, index),
+        Entry::Movie(movie) => save_entry(state.movies.entry(user_id).or_insert(vec![]), movie, index),
+        Entry::Quote(quote) => save_entry(state.quotes.entry(user_id).or_insert(vec![]), quote, index),
+        Entry::Series(series) => save_entry(state.series.entry(user_id).or_insert(vec![]), series, index),
+    }
+}
+
+/// Removes the watchlist title a movie was rated from, unless the watchlist changed meanwhile.
+fn remove_watched(state: &mut State, user_id: u64, entry: &Entry, watchlist_index: Option<usize>) {
+    let (Entry::Movie(movie), Some(index)) = (entry, watchlist_index) else {
+        return;
+    };
+    if let Some(watchlist) = state.watchlist.get_mut(&user_id) {
+        if watchlist.get(index).is_some_and(|item| item.title == movie.title) {
+            watchlist.remove(index);
+        }
+    }
+}
+
+/// Stores the pending entry at `index` or as a new one.
+fn keep(state: &mut State, user_id: u64, pending: PendingDuplicate, index: Option<usize>) {
+    remove_watched(state, user_id, &pending.entry, pending.watchlist_index);
+    store(state, user_id, pending.entry, index);
+}
+
+fn chat_id(update: &Update, user_id: u64) -> i64 {
+    match &update.content {
+        UpdateContent::Message(message) => message.chat.id,
+        UpdateContent::CallbackQuery(cb) => cb.message.as_ref().map_or(user_id as i64, |message| message.chat.id),
+        _ => user_id as i64,
+    }
+}
+
+/// Saves a dialog result and sends the dialog's confirmation. A new entry that looks like one already
+/// in the library is held back instead, until the user chooses to keep both, replace the old one or discard it.
+/// A movie rated from the watchlist leaves the watchlist once it is stored.
+pub fn save_or_confirm(state: &mut State, user_id: u64, update: &Update, entry: Entry, edit_index: Option<usize>, confirmation: Option<String>) {
+    let api = config().telegram_api();
+    let watchlist_index = state.dialogs.get(&user_id).and_then(|dialog| dialog.watchlist_index);
+    let index = match edit_index {
+        Some(_) => None,
+        None => find_duplicate(state, user_id, &entry),
+    };
+    let Some(index) = index else {
+        remove_watched(state, user_id, &entry, watchlist_index);
+        // The edited entry may have been deleted while the dialog was open
+        let text = if store(state, user_id, entry, edit_index) {
+            confirmation
+        } else {
+            Some(t!(state.language(user_id), "item.gone"))
+        };
+        if let Some(text) = text {
+            send_message(&api, chat_id(update, user_id), &text);
+        }
+        return;
+    };
+
+    // Only one question is open at a time, an unanswered one keeps both entries
+    let language = state.language(user_id);
+    if let Some(previous) = state.duplicates.remove(&user_id) {
+        let text = t!(language, "duplicate.unanswered", item = previous.entry.label(language));
+        keep(state, user_id, previous, None);
+        send_message(&api, chat_id(update, user_id), &text);
+    }
+    let existing = state.label(user_id, entry.kind(), index).unwrap_or_default();
+    let text = t!(language, "duplicate.question", item = existing);
+    let keyboard = duplicate_keyboard(language, fingerprint(&entry.label(language)));
+    state.duplicates.insert(user_id, PendingDuplicate { entry, index, watchlist_index });
+    send_message_with_keyboard(&api, chat_id(update, user_id), &text, keyboard);
+}
+
+/// Applies the user's choice to the pending entry and returns the confirmation text.
+/// Returns `None` if the question was already answered.
+pub fn resolve_duplicate(state: &mut State, user_id: u64, resolution: DuplicateResolution, expected: u32) -> Option<String> {
+    let language = state.language(user_id);
+    let pending = state.duplicates.get(&user_id).filter(|pending| fingerprint(&pending.entry.label(language)) == expected)?.clone();
+    state.duplicates.remove(&user_id);
+    let kind = pending.entry.kind();
+    let label = pending.entry.label(language);
+    let text = match resolution {
+        DuplicateResolution::KeepBoth => {
+            keep(state, user_id, pending, None);
+            t!(language, "duplicate.kept", item = label)
+        }
+        DuplicateResolution::Replace => {
+            // The old entry may have been deleted or edited while the question was open
+            let replaced = state.label(user_id, kind, pending.index)
+                .filter(|_| find_duplicate(state, user_id, &pending.entry) == Some(pending.index));
+            match replaced {
+                Some(existing) => {
+                    let index = pending.index;
+                    keep(state, user_id, pending, Some(index));
+                    t!(language, "duplicate.replaced", old = existing, new = label)
+                }
+                None => {
+                    keep(state, user_id, pending, None);
+                    t!(language, "duplicate.gone", item = label)
+                }
+            }
+        }
+        DuplicateResolution::Discard => t!(language, "duplicate.discarded", item = label),
+    };
+    Some(text)
+}
@@ -0,0 +1,50 @@
+use crate::domain::{Library, LibraryRecord};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<ExportFormat> {
+        match code.to_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("media-tracker-library.{}", self.code())
+    }
+}
+
+pub fn export(library: &Library, format: ExportFormat) -> Result<Vec<u8>, String> {
+    match format {
+        ExportFormat::Json => serde_json::to_vec_pretty(library)
+            .map_err(|err| format!("JSON serialization failed: {}", err)),
+        ExportFormat::Csv => to_csv(library),
+    }
+}
+
+fn to_csv(library: &Library) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    let records = library.books.iter().map(LibraryRecord::from)
+        .chain(library.movies.iter().map(LibraryRecord::from))
+        .chain(library.series.iter().map(LibraryRecord::from))
+        .chain(library.quotes.iter().map(LibraryRecord::from));
+    for record in records {
+        writer.serialize(record)
+            .map_err(|err| format!("CSV serialization failed: {}", err))?;
+    }
+    writer.into_inner()
+        .map_err(|err| format!("CSV serialization failed: {}", err))
+}
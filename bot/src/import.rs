@@ -0,0 +1,398 @@
+use crate::config::config;
+use crate::domain::{Book, Movie, ReadingStatus, State};
+use crate::search::normalize;
+
+use dialog_engine::{Language, RatingScale, t, validate_date, validate_year};
+use frankenstein::Document;
+use serde::Deserialize;
+use telegram_api::*;
+
+// Unparsed rows listed in the import report
+const MAX_REPORTED_ERRORS: usize = 10;
+
+/// A row of `goodreads_library_export.csv`, other columns are ignored.
+#[derive(Deserialize, Debug)]
+struct GoodreadsRow {
+    #[serde(rename = "Title")]
+    title: String,
+    #[serde(rename = "Author")]
+    author: String,
+    // 0 means the book wasn't rated
+    #[serde(rename = "My Rating")]
+    rating: String,
+    // yyyy/mm/dd, empty for books that weren't read
+    #[serde(rename = "Date Read", default)]
+    date_read: String,
+    // `read`, `currently-reading`, `to-read` or a shelf of the user
+    #[serde(rename = "Exclusive Shelf", default)]
+    shelf: String,
+}
+
+/// Books on the user's own shelves, or exported without the column, are taken as read.
+fn goodreads_shelf(shelf: &str) -> ReadingStatus {
+    match shelf.trim() {
+        "to-read" => ReadingStatus::WantToRead,
+        "currently-reading" => ReadingStatus::Reading,
+        _ => ReadingStatus::Finished,
+    }
+}
+
+/// A row of Letterboxd `ratings.csv` or `diary.csv`, other columns are ignored.
+#[derive(Deserialize, Debug)]
+struct LetterboxdRow {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Year")]
+    year: String,
+    // 0.5-5 in half stars, empty for unrated diary entries
+    #[serde(rename = "Rating")]
+    rating: String,
+    // YYYY-MM-DD, the date of the diary or rating entry
+    #[serde(rename = "Date", default)]
+    date: String,
+    // YYYY-MM-DD, only in diary.csv
+    #[serde(rename = "Watched Date", default)]
+    watched_date: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ImportSource {
+    Goodreads,
+    Letterboxd,
+}
+
+impl ImportSource {
+    fn detect(headers: &csv::StringRecord) -> Option<ImportSource> {
+        let has = |columns: &[&str]| columns.iter().all(|column| headers.iter().any(|header| header == *column));
+        if has(&["Title", "Author", "My Rating"]) {
+            Some(ImportSource::Goodreads)
+        } else if has(&["Name", "Year", "Rating"]) {
+            Some(ImportSource::Letterboxd)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: usize,
+    pub duplicates: usize,
+    // Rows that could not be parsed, with the CSV line and the reason
+    pub errors: Vec<String>,
+}
+
+impl ImportReport {
+    /// `items` is the key of the imported media type, `count.book` or `count.movie`.
+    fn summary(&self, language: Language, source: &str, items: &str) -> String {
+        let mut text = t!(
+            language,
+            "import.summary",
+            items = language.count(items, self.imported),
+            source = source,
+            skipped = self.skipped,
+            duplicates = self.duplicates,
+        );
+        if !self.errors.is_empty() {
+            text.push_str(&t!(language, "import.errors", count = self.errors.len()));
+            for error in self.errors.iter().take(MAX_REPORTED_ERRORS) {
+                text.push_str(error);
+                text.push('\n');
+            }
+            if self.errors.len() > MAX_REPORTED_ERRORS {
+                text.push_str(&t!(language, "list.more", count = self.errors.len() - MAX_REPORTED_ERRORS));
+            }
+        }
+        text
+    }
+}
+
+pub fn import_document(state: &mut State, api: &Api, chat_id: i64, user_id: u64, document: &Document) {
+    let language = state.language(user_id);
+    let is_csv = document.file_name.as_ref().is_some_and(|name| name.to_lowercase().ends_with(".csv"));
+    if !is_csv {
+        send_message(api, chat_id, &t!(language, "import.not_csv"));
+        return;
+    }
+    let max_size = config().limits.import_size;
+    if document.file_size.is_some_and(|size| size > max_size) {
+        send_message(api, chat_id, &t!(language, "import.too_large", size = format!("{:.1}", max_size as f64 / 1_000_000.0)));
+        return;
+    }
+    let content = match download_file(api, &document.file_id) {
+        Ok(content) => content,
+        Err(err) => {
+            println!("Error downloading document: {:?}", err);
+            send_message(api, chat_id, &t!(language, "import.download_failed"));
+            return;
+        }
+    };
+    let headers = csv::Reader::from_reader(content.as_slice()).headers().cloned();
+    let result = match headers.as_ref().ok().and_then(ImportSource::detect) {
+        Some(ImportSource::Goodreads) => import_goodreads(state, user_id, &content)
+            .map(|report| report.summary(language, "Goodreads", "count.book")),
+        Some(ImportSource::Letterboxd) => import_letterboxd(state, user_id, &content)
+            .map(|report| report.summary(language, "Letterboxd", "count.movie")),
+        None => Err(t!(language, "import.unknown")),
+    };
+    match result {
+        Ok(text) => send_message(api, chat_id, &text),
+        Err(err) => send_message(api, chat_id, &err),
+    }
+}
+
+fn same_book(book: &Book, title: &str, author: &str) -> bool {
+    normalize(book.title.trim()) == normalize(title.trim()) && normalize(book.author.trim()) == normalize(author.trim())
+}
+
+fn same_movie(movie: &Movie, title: &str, year: u32) -> bool {
+    normalize(movie.title.trim()) == normalize(title.trim()) && movie.year == Some(year)
+}
+
+/// Parses a Goodreads export and appends its books to their shelves, oldest read first.
+/// Read books are skipped unless they are rated.
+pub fn import_goodreads(state: &mut State, user_id: u64, content: &[u8]) -> Result<ImportReport, String> {
+    let language = state.language(user_id);
+    let mut reader = csv::Reader::from_reader(content);
+    let mut report = ImportReport::default();
+    let mut rows = vec![];
+    for (line, row) in reader.deserialize::<GoodreadsRow>().enumerate() {
+        match row {
+            Ok(row) => rows.push(row),
+            // Line 1 is the header
+            Err(err) => report.errors.push(t!(language, "import.line", line = line + 2, error = err)),
+        }
+    }
+    // Goodreads lists the most recently added books first, import in reading order instead
+    rows.sort_by(|a, b| a.date_read.cmp(&b.date_read));
+
+    let books = state.books.entry(user_id).or_insert(vec![]);
+    for row in rows {
+        let status = goodreads_shelf(&row.shelf);
+        // Goodreads rates on 1-5 stars, 0 stands for no rating. Only finished books have a rating and a date read
+        let (rating, date) = if status != ReadingStatus::Finished {
+            (None, None)
+        } else {
+            match RatingScale::FiveStars.parse(language, &row.rating) {
+                Ok(rating) => (Some(rating), validate_date(&row.date_read).ok()),
+                Err(_) => {
+                    report.skipped += 1;
+                    continue;
+                }
+            }
+        };
+        if row.title.trim().is_empty() || row.author.trim().is_empty() {
+            report.errors.push(t!(language, "import.title_author_required", title = row.title));
+            continue;
+        }
+        if books.iter().any(|book| same_book(book, &row.title, &row.author)) {
+            report.duplicates += 1;
+            continue;
+        }
+        books.push(Book {
+            title: row.title.trim().to_string(),
+            author: row.author.trim().to_string(),
+            status,
+            rating,
+            date,
+        });
+        report.imported += 1;
+    }
+    Ok(report)
+}
+
+/// Letterboxd rates on 0.5-5 stars in half stars, empty for unrated diary entries.
+fn letterboxd_rating(language: Language, text: &str) -> Result<Option<u32>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    RatingScale::HalfStars.parse(language, text).map(Some)
+}
+
+/// Parses a Letterboxd `ratings.csv` or `diary.csv` and appends its rated movies.
+pub fn import_letterboxd(state: &mut State, user_id: u64, content: &[u8]) -> Result<ImportReport, String> {
+    let language = state.language(user_id);
+    let mut reader = csv::Reader::from_reader(content);
+    let mut report = ImportReport::default();
+    let movies = state.movies.entry(user_id).or_insert(vec![]);
+    for (line, row) in reader.deserialize::<LetterboxdRow>().enumerate() {
+        // Line 1 is the header
+        let line = line + 2;
+        let row = match row {
+            Ok(row) => row,
+            Err(err) => {
+                report.errors.push(t!(language, "import.line", line = line, error = err));
+                continue;
+            }
+        };
+        if row.name.trim().is_empty() {
+            report.errors.push(t!(language, "import.line", line = line, error = t!(language, "import.title_missing")));
+            continue;
+        }
+        let year = match validate_year(&row.year.trim().to_string()) {
+            Ok(year) => year,
+            Err(key) => {
+                report.errors.push(t!(language, "import.line_title", line = line, title = row.name, error = t!(language, key)));
+                continue;
+            }
+        };
+        let rating = match letterboxd_rating(language, &row.rating) {
+            Ok(Some(rating)) => rating,
+            Ok(None) => {
+                report.skipped += 1;
+                continue;
+            }
+            Err(err) => {
+                report.errors.push(t!(language, "import.line_title", line = line, title = row.name, error = err));
+                continue;
+            }
+        };
+        // Diary entries repeat for rewatches
+        if movies.iter().any(|movie| same_movie(movie, &row.name, year)) {
+            report.duplicates += 1;
+            continue;
+        }
+        movies.push(Movie {
+            title: row.name.trim().to_string(),
+            year: Some(year),
+            rating,
+            // ratings.csv only knows when the movie was rated
+            date: validate_date(&row.watched_date).or_else(|_| validate_date(&row.date)).ok(),
+        });
+        report.imported += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dialog_engine::MAX_SCORE;
+
+    const USER_ID: u64 = 1;
+
+    #[test]
+    fn detects_the_export_by_its_columns() {
+        let goodreads = csv::StringRecord::from(vec!["Book Id", "Title", "Author", "My Rating", "Date Read"]);
+        let letterboxd = csv::StringRecord::from(vec!["Date", "Name", "Year", "Letterboxd URI", "Rating"]);
+        let other = csv::StringRecord::from(vec!["Title", "Rating"]);
+        assert_eq!(ImportSource::detect(&goodreads), Some(ImportSource::Goodreads));
+        assert_eq!(ImportSource::detect(&letterboxd), Some(ImportSource::Letterboxd));
+        assert_eq!(ImportSource::detect(&other), None);
+    }
+
+    #[test]
+    fn imports_rated_goodreads_books_in_reading_order() {
+        let mut state = State::empty();
+        let content = "Book Id,Title,Author,My Rating,Date Read\n\
+            1,Dune,Frank Herbert,5,2021/03/04\n\
+            2,Unrated,Someone,0,\n\
+            3,The Hobbit,J.R.R. Tolkien,4,2019/01/15\n\
+            4,dune,FRANK HERBERT,3,2022/01/01\n\
+            5, ,Nobody,4,\n\
+            6,Too,Many,Columns,4,\n";
+        let report = import_goodreads(&mut state, USER_ID, content.as_bytes()).unwrap();
+
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.errors.len(), 2);
+        let books = &state.books[&USER_ID];
+        assert_eq!(books[0].title, "The Hobbit");
+        assert_eq!(books[0].rating, Some(80));
+        assert_eq!(books[0].date.as_deref(), Some("2019-01-15"));
+        assert_eq!(books[0].status, ReadingStatus::Finished);
+        assert_eq!(books[1].title, "Dune");
+        assert_eq!(books[1].rating, Some(MAX_SCORE));
+        assert_eq!(books[1].date.as_deref(), Some("2021-03-04"));
+    }
+
+    #[test]
+    fn imports_goodreads_books_to_their_shelves() {
+        let mut state = State::empty();
+        let content = "Book Id,Title,Author,My Rating,Date Read,Exclusive Shelf\n\
+            1,Dune,Frank Herbert,5,2021/03/04,read\n\
+            2,Unrated,Someone,0,,read\n\
+            3,The Hobbit,J.R.R. Tolkien,0,,to-read\n\
+            4,Solaris,Stanislaw Lem,4,2020/01/01,currently-reading\n\
+            5,Neuromancer,William Gibson,3,2019/05/06,favorites\n";
+        let report = import_goodreads(&mut state, USER_ID, content.as_bytes()).unwrap();
+
+        assert_eq!(report.imported, 4);
+        assert_eq!(report.skipped, 1);
+        let books = &state.books[&USER_ID];
+        let book = |title: &str| books.iter().find(|book| book.title == title).unwrap();
+        assert_eq!(book("The Hobbit").status, ReadingStatus::WantToRead);
+        assert_eq!(book("The Hobbit").rating, None);
+        assert_eq!(book("Solaris").status, ReadingStatus::Reading);
+        assert_eq!(book("Solaris").rating, None);
+        assert_eq!(book("Solaris").date, None);
+        assert_eq!(book("Dune").status, ReadingStatus::Finished);
+        assert_eq!(book("Dune").rating, Some(MAX_SCORE));
+        assert_eq!(book("Neuromancer").status, ReadingStatus::Finished);
+        assert_eq!(book("Neuromancer").date.as_deref(), Some("2019-05-06"));
+    }
+
+    #[test]
+    fn goodreads_books_already_in_the_library_are_duplicates() {
+        let mut state = State::empty();
+        state.books.insert(USER_ID, vec![Book {
+            title: "Mémoires d'Hadrien".to_string(),
+            author: "Marguerite Yourcenar".to_string(),
+            status: ReadingStatus::Reading,
+            rating: None,
+            date: None,
+        }]);
+        let content = "Title,Author,My Rating,Date Read\nMemoires d'Hadrien,marguerite yourcenar,4,\n";
+        let report = import_goodreads(&mut state, USER_ID, content.as_bytes()).unwrap();
+
+        assert_eq!(report.imported, 0);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(state.books[&USER_ID].len(), 1);
+    }
+
+    #[test]
+    fn letterboxd_ratings_are_half_stars() {
+        let language = Language::English;
+        assert_eq!(letterboxd_rating(language, ""), Ok(None));
+        assert_eq!(letterboxd_rating(language, "0.5"), Ok(Some(10)));
+        assert_eq!(letterboxd_rating(language, "3.5"), Ok(Some(70)));
+        assert_eq!(letterboxd_rating(language, "5"), Ok(Some(MAX_SCORE)));
+        assert!(letterboxd_rating(language, "4.3").is_err());
+        assert!(letterboxd_rating(language, "6").is_err());
+    }
+
+    #[test]
+    fn imports_rated_letterboxd_movies() {
+        let mut state = State::empty();
+        let content = "Date,Name,Year,Letterboxd URI,Rating\n\
+            2023-01-02,Dune,2021,https://boxd.it/1,4.5\n\
+            2023-01-03,Old Movie,1850,https://boxd.it/2,3\n\
+            2023-01-04,Unrated,2020,https://boxd.it/3,\n\
+            2023-01-05,Dune,2021,https://boxd.it/4,4\n\
+            2023-01-06,Odd Rating,2020,https://boxd.it/5,4.3\n";
+        let report = import_letterboxd(&mut state, USER_ID, content.as_bytes()).unwrap();
+
+        assert_eq!(report.imported, 1);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.errors.len(), 2);
+        let movie = &state.movies[&USER_ID][0];
+        assert_eq!(movie.title, "Dune");
+        assert_eq!(movie.year, Some(2021));
+        assert_eq!(movie.rating, 90);
+        assert_eq!(movie.date.as_deref(), Some("2023-01-02"));
+    }
+
+    #[test]
+    fn letterboxd_diary_prefers_the_watched_date() {
+        let mut state = State::empty();
+        let content = "Date,Name,Year,Letterboxd URI,Rating,Rewatch,Tags,Watched Date\n\
+            2023-02-10,Arrival,2016,https://boxd.it/6,5,,,2023-02-08\n";
+        let report = import_letterboxd(&mut state, USER_ID, content.as_bytes()).unwrap();
+
+        assert_eq!(report.imported, 1);
+        assert_eq!(state.movies[&USER_ID][0].date.as_deref(), Some("2023-02-08"));
+    }
+}
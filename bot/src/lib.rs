@@ -1,23 +1,75 @@
+// Natively the library is only used by the local runner in `main.rs`
+#![cfg_attr(not(any(target_arch = "wasm32", feature = "local")), allow(dead_code))]
+
+#[cfg(target_arch = "wasm32")]
 cargo_component_bindings::generate!();
 
 mod add_book_dialog;
 mod add_movie_dialog;
 mod add_quote_dialog;
+mod add_series_dialog;
 mod bot;
+mod callbacks;
+mod config;
+mod dialog_runtime;
 mod dialogs;
-mod env;
+mod duplicates;
+mod export;
+mod import;
+mod lists;
 mod domain;
+mod search;
+mod stats;
+mod storage;
+// Webhooks are set up through the Golem API, the local runner polls
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+mod webhook;
 mod workers;
 
 
+#[cfg(target_arch = "wasm32")]
 use crate::bindings::exports::golem::template::api::*;
 
+#[cfg(target_arch = "wasm32")]
 struct Component;
 
+/// Loads the configuration, then the state, the first time the bot is called.
+fn start() -> Result<(), String> {
+    let config = config::load()?;
+    domain::with_state(|state| state.start(config))
+}
+
+#[cfg(target_arch = "wasm32")]
 impl Guest for Component {
     fn start_bot() {
+        if let Err(err) = start() {
+            println!("Error starting the bot: {}", err);
+            return;
+        }
         domain::with_state(|state| {
             bot::handle_updates(state);
         })
     }
+
+    fn set_webhook(url: String) -> Result<(), String> {
+        start()?;
+        webhook::set_webhook_url(&url)
+    }
+
+    fn handle_update(update: String, secret_token: Option<String>) -> Result<(), String> {
+        start()?;
+        domain::with_state(|state| {
+            webhook::handle_webhook_update(state, &update, secret_token.as_deref())
+        })
+    }
+}
+
+/// Runs the bot natively by long polling, with the in-process dialog runtime unless `DIALOG_RUNTIME` says otherwise.
+#[cfg(feature = "local")]
+pub fn run_local() -> Result<(), String> {
+    start()?;
+    domain::with_state(|state| {
+        bot::handle_updates(state);
+    });
+    Ok(())
 }
@@ -0,0 +1,219 @@
+use crate::callbacks::{CallbackAction, button, fingerprint, navigation_row};
+use crate::config::config;
+use crate::domain::{Book, MediaKind, ReadingStatus, State, WatchlistItem};
+
+use dialog_engine::{Language, RatingScale, t};
+use frankenstein::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+use std::ops::Range;
+
+// Telegram rejects messages longer than 4096 characters
+pub const MAX_MESSAGE_CHARS: usize = 4096;
+
+fn book_line(book: &Book, scale: RatingScale, language: Language) -> String {
+    let (title, author) = (&book.title, &book.author);
+    match (book.status, book.rating.map(|rating| scale.format(rating)), &book.date) {
+        (ReadingStatus::Finished, Some(rating), Some(date)) =>
+            t!(language, "list.book.rated_on", title = title, author = author, rating = rating, date = date),
+        (ReadingStatus::Finished, Some(rating), None) => t!(language, "list.book.rated", title = title, author = author, rating = rating),
+        (status, _, _) => t!(language, "list.book.shelved", title = title, author = author, shelf = status.name(language)),
+    }
+}
+
+pub fn entry_lines(state: &State, user_id: u64, kind: MediaKind) -> Vec<String> {
+    let scale = state.rating_scale(user_id);
+    let language = state.language(user_id);
+    match kind {
+        MediaKind::Book => state.books.get(&user_id).map(|books| books.iter().map(|book| book_line(book, scale, language)).collect()),
+        MediaKind::Movie => state.movies.get(&user_id).map(|movies| movies.iter()
+            .map(|movie| match &movie.date {
+                Some(date) => t!(language, "list.movie.rated_on", movie = movie.label(), rating = scale.format(movie.rating), date = date),
+                None => t!(language, "list.movie.rated", movie = movie.label(), rating = scale.format(movie.rating)),
+            })
+            .collect()),
+        MediaKind::Series => state.series.get(&user_id).map(|series| series.iter()
+            .map(|series| {
+                let seasons = language.count("series.seasons_watched", series.seasons as usize);
+                t!(language, "list.series", title = series.title, seasons = seasons, rating = scale.format(series.rating))
+            })
+            .collect()),
+        MediaKind::Quote => state.quotes.get(&user_id).map(|quotes| quotes.iter()
+            .map(|quote| {
+                let source = match &quote.author {
+                    Some(author) => t!(language, "quote.source_by", text = quote.text, title = quote.title, author = author),
+                    None => t!(language, "quote.source", text = quote.text, title = quote.title),
+                };
+                match &quote.date {
+                    Some(date) => t!(language, "list.quote.saved_on", quote = source, date = date),
+                    None => source,
+                }
+            })
+            .collect()),
+    }.unwrap_or_default()
+}
+
+fn separator(kind: MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Quote => "\n\n",
+        _ => "\n",
+    }
+}
+
+pub fn truncate(line: &str, max_chars: usize) -> String {
+    if line.chars().count() <= max_chars {
+        line.to_string()
+    } else {
+        let mut truncated: String = line.chars().take(max_chars - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// Splits the lines into pages of at most `page_size` entries that fit into one message
+/// along with `reserved` characters of header, lines longer than that are truncated to fit.
+pub fn pages(lines: &[String], separator: &str, reserved: usize) -> Vec<Range<usize>> {
+    let mut pages = vec![];
+    let mut start = 0;
+    let mut chars = 0;
+    let page_size = config().limits.page_size;
+    let budget = MAX_MESSAGE_CHARS - reserved;
+    for (index, line) in lines.iter().enumerate() {
+        let line_chars = line.chars().count().min(budget - separator.len()) + separator.len();
+        if index > start && (index - start == page_size || chars + line_chars > budget) {
+            pages.push(start..index);
+            start = index;
+            chars = 0;
+        }
+        chars += line_chars;
+    }
+    if start < lines.len() {
+        pages.push(start..lines.len());
+    }
+    pages
+}
+
+/// The header of a page of the list titled `title`, numbered if there are several pages.
+fn page_header(language: Language, title: &str, page: usize, pages: usize) -> String {
+    if pages > 1 {
+        t!(language, "list.page", title = title, page = page + 1, pages = pages)
+    } else {
+        format!("{}:\n", title)
+    }
+}
+
+/// Characters to reserve for the header of any page of a list of `lines` entries.
+fn header_chars(language: Language, title: &str, lines: usize) -> usize {
+    // Page numbers have at most as many digits as the number of entries
+    page_header(language, title, lines, lines.max(2)).chars().count()
+}
+
+/// Storage indices of the entries shown in the list, books can be limited to one shelf.
+fn listed_indices(state: &State, user_id: u64, kind: MediaKind, shelf: Option<ReadingStatus>) -> Vec<usize> {
+    match (kind, shelf) {
+        (MediaKind::Book, Some(shelf)) => state.books.get(&user_id)
+            .map(|books| books.iter()
+                .enumerate()
+                .filter(|(_, book)| book.status == shelf)
+                .map(|(index, _)| index)
+                .collect())
+            .unwrap_or_default(),
+        _ => (0..entry_lines(state, user_id, kind).len()).collect(),
+    }
+}
+
+/// Renders one page of the user's list, clamping `page` to the last page.
+/// Returns `None` if the list is empty.
+pub fn render_page(state: &State, user_id: u64, kind: MediaKind, shelf: Option<ReadingStatus>, page: usize) -> Option<(String, InlineKeyboardMarkup)> {
+    let indices = listed_indices(state, user_id, kind, shelf);
+    let all_lines = entry_lines(state, user_id, kind);
+    let lines: Vec<String> = indices.iter().map(|index| all_lines[*index].clone()).collect();
+    let separator = separator(kind);
+    let language = state.language(user_id);
+    let title = t!(language, &format!("list.title.{}", kind.code()));
+    let title = match shelf {
+        Some(shelf) => format!("{} ({})", title, shelf.name(language)),
+        None => title,
+    };
+    let reserved = header_chars(language, &title, lines.len());
+    let pages = pages(&lines, separator, reserved);
+    if pages.is_empty() {
+        return None;
+    }
+    let page = page.min(pages.len() - 1);
+    let range = pages[page].clone();
+
+    let mut text = page_header(language, &title, page, pages.len());
+    for line in &lines[range.clone()] {
+        text.push_str(&truncate(line, MAX_MESSAGE_CHARS - reserved - separator.len()));
+        text.push_str(separator);
+    }
+
+    let labels = state.labels(user_id, kind);
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = range
+        .map(|position| {
+            let index = indices[position];
+            let action = CallbackAction::Delete(kind, index, fingerprint(&labels[index]));
+            vec![button(&t!(language, "list.delete", item = labels[index]), action)]
+        })
+        .collect();
+    rows.extend(navigation_row(language, page, pages.len(), |page| CallbackAction::Page(kind, shelf, page)));
+
+    let keyboard = InlineKeyboardMarkup::builder()
+        .inline_keyboard(rows)
+        .build();
+    Some((text, keyboard))
+}
+
+fn watchlist_labels(state: &State, user_id: u64) -> Vec<String> {
+    state.watchlist.get(&user_id)
+        .map(|watchlist| watchlist.iter().map(WatchlistItem::label).collect())
+        .unwrap_or_default()
+}
+
+/// Pages of the watchlist, the same for rendering a page and finding the page of a title.
+fn watchlist_pages(language: Language, labels: &[String]) -> Vec<Range<usize>> {
+    pages(labels, "\n", header_chars(language, &t!(language, "watchlist.title"), labels.len()))
+}
+
+/// The page of the watchlist showing the title at `index`, past the last page if there is no such title.
+pub fn watchlist_page(state: &State, user_id: u64, index: usize) -> usize {
+    watchlist_pages(state.language(user_id), &watchlist_labels(state, user_id)).iter()
+        .position(|range| range.contains(&index))
+        .unwrap_or(usize::MAX)
+}
+
+/// Renders one page of the watchlist with "Watched" and "Remove" buttons for each title,
+/// clamping `page` to the last page. Returns `None` if the watchlist is empty.
+pub fn render_watchlist(state: &State, user_id: u64, page: usize) -> Option<(String, InlineKeyboardMarkup)> {
+    let language = state.language(user_id);
+    let labels = watchlist_labels(state, user_id);
+    let pages = watchlist_pages(language, &labels);
+    if pages.is_empty() {
+        return None;
+    }
+    let page = page.min(pages.len() - 1);
+    let range = pages[page].clone();
+
+    let title = t!(language, "watchlist.title");
+    let mut text = page_header(language, &title, page, pages.len());
+    let max_chars = MAX_MESSAGE_CHARS - header_chars(language, &title, labels.len()) - 1;
+    for label in &labels[range.clone()] {
+        text.push_str(&truncate(label, max_chars));
+        text.push('\n');
+    }
+
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = range
+        .map(|index| {
+            let fingerprint = fingerprint(&labels[index]);
+            vec![
+                button(&t!(language, "watchlist.watched", item = labels[index]), CallbackAction::Watched(index, fingerprint)),
+                button(&t!(language, "watchlist.remove"), CallbackAction::Unwatch(index, fingerprint)),
+            ]
+        })
+        .collect();
+    rows.extend(navigation_row(language, page, pages.len(), CallbackAction::WatchlistPage));
+    let keyboard = InlineKeyboardMarkup::builder()
+        .inline_keyboard(rows)
+        .build();
+    Some((text, keyboard))
+}
@@ -0,0 +1,14 @@
+//! Local runner for development: the whole bot as a native program, without Golem.
+//! Dialogs are called in-process, and setting `TELEGRAM_API_URL` points the bot
+//! and the dialogs at another Bot API server, e.g. a stub:
+//!
+//! ```text
+//! TELEGRAM_TOKEN=123:test TELEGRAM_API_URL=http://localhost:8081 cargo run --bin local_bot --features local
+//! ```
+
+fn main() {
+    if let Err(err) = golem_telegram_bot::run_local() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::config::config;
+use crate::domain::{MediaKind, State};
+use crate::lists::{MAX_MESSAGE_CHARS, entry_lines, truncate};
+
+use dialog_engine::{Language, t};
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+/// Lowercases the text and strips diacritics, so "Mémoires" matches "memoires".
+pub fn normalize(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn words(text: &str) -> Vec<String> {
+    normalize(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn word_score(query: &str, word: &str) -> u32 {
+    let query_len = query.chars().count();
+    // Short words only tolerate a single typo
+    let max_typos = if query_len <= 4 { 1 } else { 2 };
+    if word == query {
+        4
+    } else if word.starts_with(query) {
+        3
+    } else if word.contains(query) {
+        2
+    } else if query_len >= 3 && levenshtein(query, word) <= max_typos {
+        1
+    } else {
+        0
+    }
+}
+
+/// Scores the fields of an entry against the query, every query word has to match something.
+fn score(query: &str, fields: &[String]) -> Option<u32> {
+    let query_words = words(query);
+    if query_words.is_empty() {
+        return None;
+    }
+    let field_words: Vec<String> = fields.iter().flat_map(|field| words(field)).collect();
+    let mut total = 0;
+    for query_word in &query_words {
+        let best = field_words.iter().map(|word| word_score(query_word, word)).max().unwrap_or(0);
+        if best == 0 {
+            return None;
+        }
+        total += best;
+    }
+    let normalized_query = normalize(query.trim());
+    if fields.iter().any(|field| normalize(field).contains(&normalized_query)) {
+        total += 2;
+    }
+    Some(total)
+}
+
+fn entry_fields(state: &State, user_id: u64, kind: MediaKind) -> Vec<Vec<String>> {
+    match kind {
+        MediaKind::Book => state.books.get(&user_id).map(|books| books.iter()
+            .map(|book| vec![book.title.clone(), book.author.clone()])
+            .collect()),
+        MediaKind::Movie => state.movies.get(&user_id).map(|movies| movies.iter()
+            .map(|movie| vec![movie.title.clone(), movie.year.map(|year| year.to_string()).unwrap_or_default()])
+            .collect()),
+        MediaKind::Series => state.series.get(&user_id).map(|series| series.iter()
+            .map(|series| vec![series.title.clone()])
+            .collect()),
+        MediaKind::Quote => state.quotes.get(&user_id).map(|quotes| quotes.iter()
+            .map(|quote| vec![quote.text.clone(), quote.title.clone(), quote.author.clone().unwrap_or_default()])
+            .collect()),
+    }.unwrap_or_default()
+}
+
+/// Indices of the matching entries of the given kind, best matches first.
+pub fn search(state: &State, user_id: u64, kind: MediaKind, query: &str) -> Vec<usize> {
+    let mut matches: Vec<(usize, u32)> = entry_fields(state, user_id, kind)
+        .iter()
+        .enumerate()
+        .filter_map(|(index, fields)| score(query, fields).map(|score| (index, score)))
+        .collect();
+    // Stable sort keeps the library order for equal scores
+    matches.sort_by(|a, b| b.1.cmp(&a.1));
+    matches.into_iter().map(|(index, _)| index).collect()
+}
+
+/// Renders a group of results with as many whole lines as fit into `budget` characters along with
+/// the header and the "…and N more" footer, only a first line too long to fit alone is truncated.
+fn render_group(language: Language, header: &str, lines: &[&String], total: usize, budget: usize) -> Option<String> {
+    let mut group = format!("\n{}:\n", header);
+    // The footer is longest when no line is shown
+    let reserved = group.chars().count() + t!(language, "list.more", count = total).chars().count();
+    let mut chars = reserved;
+    let mut shown = 0;
+    for line in lines {
+        let line_chars = line.chars().count() + 1;
+        if chars + line_chars > budget {
+            if shown == 0 && chars + 2 < budget {
+                group.push_str(&truncate(line, budget - chars - 1));
+                group.push('\n');
+                shown = 1;
+            }
+            break;
+        }
+        group.push_str(line);
+        group.push('\n');
+        chars += line_chars;
+        shown += 1;
+    }
+    if shown == 0 {
+        return None;
+    }
+    if total > shown {
+        group.push_str(&t!(language, "list.more", count = total - shown));
+    }
+    Some(group)
+}
+
+pub fn render_results(state: &State, user_id: u64, query: &str) -> String {
+    let language = state.language(user_id);
+    let mut text = String::new();
+    let mut found = false;
+    let max_results = config().limits.search_results;
+    let mut budget = MAX_MESSAGE_CHARS
+        .saturating_sub(t!(language, "search.results", query = query, results = "").chars().count());
+    for kind in [MediaKind::Book, MediaKind::Movie, MediaKind::Series, MediaKind::Quote] {
+        let indices = search(state, user_id, kind, query);
+        if indices.is_empty() {
+            continue;
+        }
+        found = true;
+        let lines = entry_lines(state, user_id, kind);
+        let header = t!(language, &format!("search.header.{}", kind.code()));
+        let shown: Vec<&String> = indices.iter().take(max_results).map(|index| &lines[*index]).collect();
+        // Groups that don't fit are left out, like the lines past the end of a group
+        if let Some(group) = render_group(language, &header, &shown, indices.len(), budget) {
+            budget -= group.chars().count();
+            text.push_str(&group);
+        }
+    }
+
+    if !found {
+        t!(language, "search.nothing", query = query)
+    } else {
+        t!(language, "search.results", query = query, results = text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn normalize_strips_diacritics_and_case() {
+        assert_eq!(normalize("Mémoires d'Hadrien"), "memoires d'hadrien");
+        assert_eq!(normalize("ÅNGSTRÖM"), "angstrom");
+        assert_eq!(normalize("Анна Каренина"), "анна каренина");
+    }
+
+    #[test]
+    fn words_split_on_punctuation() {
+        assert_eq!(words("Harry Potter: the Philosopher's Stone"), vec!["harry", "potter", "the", "philosopher", "s", "stone"]);
+        assert!(words(" -- ").is_empty());
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("dune", "dune"), 0);
+        assert_eq!(levenshtein("dune", "dunes"), 1);
+        assert_eq!(levenshtein("tolkein", "tolkien"), 2);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn word_score_prefers_closer_matches() {
+        assert_eq!(word_score("dune", "dune"), 4);
+        assert_eq!(word_score("dun", "dune"), 3);
+        assert_eq!(word_score("une", "dune"), 2);
+        assert_eq!(word_score("tolkein", "tolkien"), 1);
+        assert_eq!(word_score("dnue", "dune"), 0);
+        // Too short to tolerate typos
+        assert_eq!(word_score("ab", "ac"), 0);
+    }
+
+    #[test]
+    fn score_requires_every_query_word() {
+        let book = fields(&["The Hobbit", "J. R. R. Tolkien"]);
+        assert!(score("hobbit tolkien", &book).is_some());
+        assert!(score("hobbit rowling", &book).is_none());
+        assert!(score("   ", &book).is_none());
+    }
+
+    #[test]
+    fn score_tolerates_typos_and_diacritics() {
+        let book = fields(&["Mémoires d'Hadrien", "Marguerite Yourcenar"]);
+        assert!(score("memoires", &book).is_some());
+        assert!(score("yourcenr", &book).is_some());
+    }
+
+    #[test]
+    fn render_group_adds_whole_lines_within_the_budget() {
+        let lines: Vec<String> = (1..=5).map(|n| format!("Book number {}", n)).collect();
+        let lines: Vec<&String> = lines.iter().collect();
+        let all = render_group(Language::English, "Books", &lines, 7, MAX_MESSAGE_CHARS).unwrap();
+        assert!(all.ends_with("Book number 5\n…and 2 more\n"));
+
+        let budget = all.chars().count() - 1;
+        let group = render_group(Language::English, "Books", &lines, 7, budget).unwrap();
+        assert!(group.chars().count() <= budget);
+        assert!(group.ends_with("Book number 4\n…and 3 more\n"));
+    }
+
+    #[test]
+    fn render_group_truncates_only_a_first_line_too_long() {
+        let long = "a".repeat(100);
+        let lines = [&long];
+        let group = render_group(Language::English, "Quotes", &lines, 1, 50).unwrap();
+        assert!(group.chars().count() <= 50);
+        assert!(group.ends_with("…\n"));
+        assert!(render_group(Language::English, "Quotes", &lines, 1, 10).is_none());
+    }
+
+    #[test]
+    fn score_ranks_exact_phrases_first() {
+        let exact = score("the hobbit", &fields(&["The Hobbit", "Tolkien"])).unwrap();
+        let scattered = score("the hobbit", &fields(&["Hobbit Tales", "The Author"])).unwrap();
+        let prefix = score("hob", &fields(&["The Hobbit", "Tolkien"])).unwrap();
+        let typo = score("hobit", &fields(&["The Hobbit", "Tolkien"])).unwrap();
+        assert!(exact > scattered);
+        assert!(prefix > typo);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::domain::{ReadingStatus, State};
+use crate::search::normalize;
+
+use dialog_engine::{Language, RatingScale, t};
+
+use std::collections::BTreeMap;
+
+const TOP_SIZE: usize = 5;
+
+#[derive(Default)]
+struct YearCounts {
+    books: usize,
+    movies: usize,
+    quotes: usize,
+}
+
+fn year_of(date: &Option<String>) -> Option<String> {
+    date.as_ref().and_then(|date| date.get(..4)).map(|year| year.to_string())
+}
+
+/// Counts names case- and diacritic-insensitively, keeping the first spelling seen.
+/// Returns the most frequent names first, ties in alphabetical order.
+fn top_names<'a>(names: impl Iterator<Item = &'a String>) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, (String, usize)> = BTreeMap::new();
+    for name in names {
+        let entry = counts.entry(normalize(name.trim())).or_insert((name.trim().to_string(), 0));
+        entry.1 += 1;
+    }
+    let mut top: Vec<(String, usize)> = counts.into_values().collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1));
+    top.truncate(TOP_SIZE);
+    top
+}
+
+/// Renders the average and the distribution of the scores on the user's scale.
+fn render_ratings(text: &mut String, header: &str, ratings: &[u32], scale: RatingScale, language: Language) {
+    if ratings.is_empty() {
+        return;
+    }
+    let average = ratings.iter().sum::<u32>() as f64 / ratings.len() as f64;
+    text.push_str(&t!(language, "stats.average", ratings = header, average = scale.format_average(average)));
+    for score in scale.scores().into_iter().rev() {
+        let count = ratings.iter().filter(|rating| scale.round(**rating) == score).count();
+        text.push_str(&format!("{} {} {}\n", scale.format(score), "★".repeat(count.min(20)), count));
+    }
+}
+
+pub fn render_stats(state: &State, user_id: u64) -> String {
+    let language = state.language(user_id);
+    let library = state.library(user_id);
+    if library.is_empty() {
+        return t!(language, "stats.empty");
+    }
+
+    let mut text = t!(
+        language,
+        "stats.library",
+        books = language.count("count.book", library.books.len()),
+        movies = language.count("count.movie", library.movies.len()),
+        series = language.count("count.series", library.series.len()),
+        quotes = language.count("count.quote", library.quotes.len()),
+    );
+
+    let shelves: Vec<String> = ReadingStatus::ALL.iter()
+        .map(|shelf| (shelf, library.books.iter().filter(|book| book.status == *shelf).count()))
+        .filter(|(_, count)| *count > 0)
+        .map(|(shelf, count)| format!("{} {}", count, shelf.name(language)))
+        .collect();
+    if !shelves.is_empty() {
+        text.push_str(&t!(language, "stats.shelves", shelves = shelves.join(", ")));
+    }
+    let finished_books: Vec<_> = library.books.iter().filter(|book| book.status == ReadingStatus::Finished).collect();
+
+    // Entries without a date are counted separately, series aren't dated
+    let mut years: BTreeMap<Option<String>, YearCounts> = BTreeMap::new();
+    for book in &finished_books {
+        years.entry(year_of(&book.date)).or_default().books += 1;
+    }
+    for movie in library.movies {
+        years.entry(year_of(&movie.date)).or_default().movies += 1;
+    }
+    for quote in library.quotes {
+        years.entry(year_of(&quote.date)).or_default().quotes += 1;
+    }
+    if !years.is_empty() {
+        text.push_str(&t!(language, "stats.by_year"));
+    }
+    // Most recent years first, undated entries last
+    for (year, counts) in years.iter().rev() {
+        let mut parts = vec![];
+        if counts.books > 0 {
+            parts.push(language.count("count.book", counts.books));
+        }
+        if counts.movies > 0 {
+            parts.push(language.count("count.movie", counts.movies));
+        }
+        if counts.quotes > 0 {
+            parts.push(language.count("count.quote", counts.quotes));
+        }
+        let year = year.clone().unwrap_or(t!(language, "stats.no_date"));
+        text.push_str(&format!("{}: {}\n", year, parts.join(", ")));
+    }
+
+    let scale = state.rating_scale(user_id);
+    let book_ratings: Vec<u32> = library.books.iter().filter_map(|book| book.rating).collect();
+    render_ratings(&mut text, &t!(language, "stats.book_ratings"), &book_ratings, scale, language);
+    let movie_ratings: Vec<u32> = library.movies.iter().map(|movie| movie.rating).collect();
+    render_ratings(&mut text, &t!(language, "stats.movie_ratings"), &movie_ratings, scale, language);
+    let series_ratings: Vec<u32> = library.series.iter().map(|series| series.rating).collect();
+    render_ratings(&mut text, &t!(language, "stats.series_ratings"), &series_ratings, scale, language);
+
+    let authors = top_names(finished_books.iter().map(|book| &book.author));
+    if !authors.is_empty() {
+        text.push_str(&t!(language, "stats.authors"));
+        for (index, (author, count)) in authors.iter().enumerate() {
+            text.push_str(&format!("{}. {} ({})\n", index + 1, author, language.count("count.book", *count)));
+        }
+    }
+    let sources = top_names(library.quotes.iter().map(|quote| &quote.title));
+    if !sources.is_empty() {
+        text.push_str(&t!(language, "stats.sources"));
+        for (index, (source, count)) in sources.iter().enumerate() {
+            text.push_str(&format!("{}. {} ({})\n", index + 1, source, language.count("count.quote", *count)));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Book, Movie, Quote, Series, Settings};
+
+    const USER_ID: u64 = 1;
+
+    fn names(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn book(author: &str, status: ReadingStatus, rating: Option<u32>, date: Option<&str>) -> Book {
+        Book {
+            title: "Title".to_string(),
+            author: author.to_string(),
+            status,
+            rating,
+            date: date.map(str::to_string),
+        }
+    }
+
+    fn movie(rating: u32, date: Option<&str>) -> Movie {
+        Movie { title: "Title".to_string(), year: None, rating, date: date.map(str::to_string) }
+    }
+
+    fn quote(title: &str, date: Option<&str>) -> Quote {
+        Quote { text: "Text".to_string(), title: title.to_string(), author: None, date: date.map(str::to_string) }
+    }
+
+    fn series(rating: u32) -> Series {
+        Series { title: "Title".to_string(), seasons: 1, rating }
+    }
+
+    fn ratings(ratings: &[u32], scale: RatingScale) -> String {
+        let mut text = String::new();
+        render_ratings(&mut text, "Ratings", ratings, scale, Language::English);
+        text
+    }
+
+    #[test]
+    fn top_names_counts_spellings_together() {
+        let top = top_names(names(&["Tolkien", "Le Guin", "tolkien ", "Asimov", "le guin", "Tolkién"]).iter());
+        assert_eq!(top, vec![("Tolkien".to_string(), 3), ("Le Guin".to_string(), 2), ("Asimov".to_string(), 1)]);
+    }
+
+    #[test]
+    fn top_names_breaks_ties_alphabetically_and_keeps_the_top() {
+        let top = top_names(names(&["Herbert", "banks", "Asimov", "Gibson", "Clarke", "Dick", "Egan"]).iter());
+        let top: Vec<&str> = top.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(top, vec!["Asimov", "banks", "Clarke", "Dick", "Egan"]);
+        assert!(top_names(names(&[]).iter()).is_empty());
+    }
+
+    #[test]
+    fn an_empty_library_has_no_stats() {
+        assert_eq!(render_stats(&State::empty(), USER_ID), t!(Language::English, "stats.empty"));
+    }
+
+    #[test]
+    fn counts_by_year_with_undated_entries_last() {
+        let mut state = State::empty();
+        state.books.insert(USER_ID, vec![
+            book("A", ReadingStatus::Finished, Some(80), Some("2023-05-01")),
+            book("A", ReadingStatus::Finished, Some(60), Some("2024-01-02")),
+            book("B", ReadingStatus::Finished, Some(60), None),
+            // Only finished books count
+            book("C", ReadingStatus::Reading, None, Some("2022-01-01")),
+        ]);
+        state.movies.insert(USER_ID, vec![movie(60, Some("2024-03-03"))]);
+        state.quotes.insert(USER_ID, vec![quote("Dune", None)]);
+
+        let text = render_stats(&state, USER_ID);
+        assert!(text.contains("\nBy year:\n2024: 1 book, 1 movie\n2023: 1 book\nNo date: 1 book, 1 quote\n"), "{}", text);
+        assert!(!text.contains("2022"), "{}", text);
+        assert!(text.contains("\nMost read authors:\n1. A (2 books)\n2. B (1 book)\n"), "{}", text);
+        assert!(text.contains("\nMost quoted sources:\n1. Dune (1 quote)\n"), "{}", text);
+    }
+
+    #[test]
+    fn leaves_out_sections_without_data() {
+        let mut state = State::empty();
+        state.series.insert(USER_ID, vec![series(80)]);
+
+        let text = render_stats(&state, USER_ID);
+        assert!(!text.contains("By year"), "{}", text);
+        assert!(!text.contains("Book ratings"), "{}", text);
+        assert!(!text.contains("Most read authors"), "{}", text);
+        assert!(text.contains("\nSeries ratings: average 4.0\n"), "{}", text);
+    }
+
+    #[test]
+    fn shows_ratings_on_the_users_scale() {
+        let mut state = State::empty();
+        state.settings.insert(USER_ID, Settings { rating_scale: RatingScale::TenPoints, ..Settings::default() });
+        state.movies.insert(USER_ID, vec![movie(80, None)]);
+
+        let text = render_stats(&state, USER_ID);
+        assert!(text.contains("\nMovie ratings: average 8.0\n10  0\n9  0\n8 ★ 1\n"), "{}", text);
+    }
+
+    #[test]
+    fn rating_distribution_rounds_to_the_scale() {
+        let scores = [80, 80, 70, 100, 100];
+        assert_eq!(ratings(&scores, RatingScale::FiveStars), "\nRatings: average 4.3\n5 ★★ 2\n4 ★★★ 3\n3  0\n2  0\n1  0\n");
+        let half_stars = ratings(&scores, RatingScale::HalfStars);
+        assert!(half_stars.starts_with("\nRatings: average 4.3\n5 ★★ 2\n4.5  0\n4 ★★ 2\n3.5 ★ 1\n3  0\n"), "{}", half_stars);
+        let ten_points = ratings(&scores, RatingScale::TenPoints);
+        assert!(ten_points.starts_with("\nRatings: average 8.6\n10 ★★ 2\n9  0\n8 ★★ 2\n7 ★ 1\n"), "{}", ten_points);
+        assert_eq!(ratings(&[], RatingScale::FiveStars), "");
+    }
+}
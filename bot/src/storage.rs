@@ -0,0 +1,87 @@
+mod json_file;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use crate::domain::{Book, Movie, Quote, Series, Settings, WatchlistItem};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+pub use json_file::JsonFileStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+
+/// Everything kept for one user, entries in the order they are listed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UserData {
+    #[serde(default)]
+    pub books: Vec<Book>,
+    #[serde(default)]
+    pub movies: Vec<Movie>,
+    #[serde(default)]
+    pub quotes: Vec<Quote>,
+    #[serde(default)]
+    pub series: Vec<Series>,
+    #[serde(default)]
+    pub watchlist: Vec<WatchlistItem>,
+    #[serde(default)]
+    pub settings: Settings,
+}
+
+/// Where the library outlives the bot's memory. `State` works on its in-memory maps,
+/// loads them from the storage once and saves a user's data after each update of that user.
+/// Active dialogs and unanswered questions are not stored.
+pub trait Storage {
+    /// The data of every user, as last saved.
+    fn load(&mut self) -> Result<HashMap<u64, UserData>, String>;
+
+    /// Replaces the stored data of one user.
+    fn save(&mut self, user_id: u64, data: &UserData) -> Result<(), String>;
+}
+
+/// Keeps nothing outside the bot's memory, which Golem persists with the worker.
+pub struct MemoryStorage;
+
+impl Storage for MemoryStorage {
+    fn load(&mut self) -> Result<HashMap<u64, UserData>, String> {
+        Ok(HashMap::new())
+    }
+
+    fn save(&mut self, _user_id: u64, _data: &UserData) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Where the library is stored, set with the `STORAGE` environment variable:
+/// `memory` (the default), `json:<path>` or `sqlite:<path>`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageKind {
+    Memory,
+    JsonFile(String),
+    #[cfg(feature = "sqlite")]
+    Sqlite(String),
+}
+
+impl StorageKind {
+    pub fn parse(text: &str) -> Result<StorageKind, String> {
+        match text.split_once(':') {
+            None if text == "memory" => Ok(StorageKind::Memory),
+            Some(("json", path)) if !path.is_empty() => Ok(StorageKind::JsonFile(path.to_string())),
+            #[cfg(feature = "sqlite")]
+            Some(("sqlite", path)) if !path.is_empty() => Ok(StorageKind::Sqlite(path.to_string())),
+            #[cfg(not(feature = "sqlite"))]
+            Some(("sqlite", _)) => Err("sqlite:<path> requires the sqlite feature".to_string()),
+            _ => Err("must be memory, json:<path> or sqlite:<path>".to_string()),
+        }
+    }
+}
+
+pub fn open(kind: &StorageKind) -> Result<Box<dyn Storage>, String> {
+    match kind {
+        StorageKind::Memory => Ok(Box::new(MemoryStorage)),
+        StorageKind::JsonFile(path) => Ok(Box::new(JsonFileStorage::new(path))),
+        #[cfg(feature = "sqlite")]
+        StorageKind::Sqlite(path) => Ok(Box::new(SqliteStorage::open(path)?)),
+    }
+}
@@ -0,0 +1,181 @@
+use super::{Storage, UserData};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+/// One line of the file: the whole data of a user at the time it was saved.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    user_id: u64,
+    data: UserData,
+}
+
+/// Appends a JSON line per save, the last line of a user wins on load.
+/// Loading compacts the file to one line per user if it holds older ones too.
+pub struct JsonFileStorage {
+    path: PathBuf,
+}
+
+impl JsonFileStorage {
+    pub fn new(path: &str) -> JsonFileStorage {
+        JsonFileStorage { path: PathBuf::from(path) }
+    }
+
+    /// Rewrites the file with the last line of each user. The lines are written to a temporary file
+    /// which then replaces the original, so a crash leaves either the old file or the new one.
+    fn compact(&self, users: &HashMap<u64, UserData>) -> Result<(), String> {
+        let mut user_ids: Vec<&u64> = users.keys().collect();
+        user_ids.sort();
+        let mut text = String::new();
+        for user_id in user_ids {
+            let record = Record { user_id: *user_id, data: users[user_id].clone() };
+            let line = serde_json::to_string(&record)
+                .map_err(|err| format!("JSON serialization failed: {}", err))?;
+            text.push_str(&line);
+            text.push('\n');
+        }
+
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+        File::create(&temp_path)
+            .and_then(|mut file| file.write_all(text.as_bytes()).and_then(|_| file.sync_all()))
+            .map_err(|err| format!("Error writing {}: {}", temp_path.display(), err))?;
+        fs::rename(&temp_path, &self.path)
+            .map_err(|err| format!("Error replacing {}: {}", self.path.display(), err))
+    }
+}
+
+/// Whether the file is empty or ends with a complete line, a crash while saving can leave it without the newline.
+fn ends_with_newline(file: &mut File) -> std::io::Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(true);
+    }
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] == b'\n')
+}
+
+impl Storage for JsonFileStorage {
+    fn load(&mut self) -> Result<HashMap<u64, UserData>, String> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(err) => return Err(format!("Error opening {}: {}", self.path.display(), err)),
+        };
+        let mut users = HashMap::new();
+        let mut lines = 0;
+        for (line, text) in BufReader::new(file).lines().enumerate() {
+            let text = text.map_err(|err| format!("Error reading {}: {}", self.path.display(), err))?;
+            if text.trim().is_empty() {
+                continue;
+            }
+            lines += 1;
+            match serde_json::from_str::<Record>(&text) {
+                Ok(record) => {
+                    users.insert(record.user_id, record.data);
+                }
+                // A line cut short by a crash while saving, the previous line of the user still holds
+                Err(err) => println!("Skipping line {} of {}: {}", line + 1, self.path.display(), err),
+            }
+        }
+        // Older lines of a user and lines cut short are dropped
+        if lines > users.len() {
+            self.compact(&users)?;
+            println!("Compacted {} from {} lines to {}", self.path.display(), lines, users.len());
+        }
+        Ok(users)
+    }
+
+    fn save(&mut self, user_id: u64, data: &UserData) -> Result<(), String> {
+        let record = Record { user_id, data: data.clone() };
+        let mut line = serde_json::to_string(&record)
+            .map_err(|err| format!("JSON serialization failed: {}", err))?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|err| format!("Error opening {}: {}", self.path.display(), err))?;
+        // Ends a line cut short by a crash, which would otherwise swallow this one
+        if !ends_with_newline(&mut file).map_err(|err| format!("Error reading {}: {}", self.path.display(), err))? {
+            line.insert(0, '\n');
+        }
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|err| format!("Error writing {}: {}", self.path.display(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::WatchlistItem;
+
+    use std::env;
+
+    fn storage(name: &str) -> JsonFileStorage {
+        let path = env::temp_dir().join(format!("json-file-storage-{}-{}.jsonl", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        JsonFileStorage { path }
+    }
+
+    fn data(title: &str) -> UserData {
+        let mut data = UserData::default();
+        data.watchlist.push(WatchlistItem { title: title.to_string(), year: None });
+        data
+    }
+
+    fn titles(users: &HashMap<u64, UserData>, user_id: u64) -> Vec<String> {
+        users[&user_id].watchlist.iter().map(|item| item.title.clone()).collect()
+    }
+
+    #[test]
+    fn the_last_save_of_a_user_wins() {
+        let mut storage = storage("last-save");
+        storage.save(1, &data("Alien")).unwrap();
+        storage.save(2, &data("Heat")).unwrap();
+        storage.save(1, &data("Aliens")).unwrap();
+
+        let users = storage.load().unwrap();
+        assert_eq!(titles(&users, 1), vec!["Aliens"]);
+        assert_eq!(titles(&users, 2), vec!["Heat"]);
+        fs::remove_file(&storage.path).unwrap();
+    }
+
+    #[test]
+    fn a_save_after_a_torn_line_starts_a_new_line() {
+        let mut storage = storage("torn-line");
+        storage.save(1, &data("Alien")).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&storage.path).unwrap();
+        file.write_all(b"{\"user_id\":1,\"data\":{\"watch").unwrap();
+        storage.save(2, &data("Heat")).unwrap();
+
+        let users = storage.load().unwrap();
+        assert_eq!(titles(&users, 1), vec!["Alien"]);
+        assert_eq!(titles(&users, 2), vec!["Heat"]);
+        fs::remove_file(&storage.path).unwrap();
+    }
+
+    #[test]
+    fn loading_compacts_the_file_to_a_line_per_user() {
+        let mut storage = storage("compact");
+        storage.save(2, &data("Heat")).unwrap();
+        storage.save(1, &data("Alien")).unwrap();
+        storage.save(1, &data("Aliens")).unwrap();
+        storage.load().unwrap();
+
+        let text = fs::read_to_string(&storage.path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        let users = storage.load().unwrap();
+        assert_eq!(titles(&users, 1), vec!["Aliens"]);
+        assert_eq!(titles(&users, 2), vec!["Heat"]);
+        fs::remove_file(&storage.path).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+use super::{Storage, UserData};
+use crate::domain::{Book, Movie, Quote, ReadingStatus, Series, Settings, WatchlistItem};
+
+use dialog_engine::{Language, RatingScale};
+use rusqlite::{Connection, Transaction, params};
+
+use std::collections::HashMap;
+
+// One table per media type, entries keep their list order in `position`
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS books (
+        user_id INTEGER NOT NULL, position INTEGER NOT NULL,
+        title TEXT NOT NULL, author TEXT NOT NULL, status TEXT NOT NULL, rating INTEGER, date TEXT,
+        PRIMARY KEY (user_id, position)
+    );
+    CREATE TABLE IF NOT EXISTS movies (
+        user_id INTEGER NOT NULL, position INTEGER NOT NULL,
+        title TEXT NOT NULL, year INTEGER, rating INTEGER NOT NULL, date TEXT,
+        PRIMARY KEY (user_id, position)
+    );
+    CREATE TABLE IF NOT EXISTS quotes (
+        user_id INTEGER NOT NULL, position INTEGER NOT NULL,
+        text TEXT NOT NULL, title TEXT NOT NULL, author TEXT, date TEXT,
+        PRIMARY KEY (user_id, position)
+    );
+    CREATE TABLE IF NOT EXISTS series (
+        user_id INTEGER NOT NULL, position INTEGER NOT NULL,
+        title TEXT NOT NULL, seasons INTEGER NOT NULL, rating INTEGER NOT NULL,
+        PRIMARY KEY (user_id, position)
+    );
+    CREATE TABLE IF NOT EXISTS watchlist (
+        user_id INTEGER NOT NULL, position INTEGER NOT NULL,
+        title TEXT NOT NULL, year INTEGER,
+        PRIMARY KEY (user_id, position)
+    );
+    CREATE TABLE IF NOT EXISTS settings (
+        user_id INTEGER PRIMARY KEY,
+        rating_scale TEXT NOT NULL, language TEXT, telegram_language TEXT
+    );
+";
+
+const TABLES: [&str; 6] = ["books", "movies", "quotes", "series", "watchlist", "settings"];
+
+/// Stores every entry as a row, so the library can be inspected and queried with any SQLite client.
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+fn sql_error(err: rusqlite::Error) -> String {
+    format!("SQLite error: {}", err)
+}
+
+impl SqliteStorage {
+    pub fn open(path: &str) -> Result<SqliteStorage, String> {
+        let connection = Connection::open(path).map_err(sql_error)?;
+        connection.execute_batch(SCHEMA).map_err(sql_error)?;
+        Ok(SqliteStorage { connection })
+    }
+
+    fn load_users(&self) -> rusqlite::Result<HashMap<u64, UserData>> {
+        let mut users: HashMap<u64, UserData> = HashMap::new();
+
+        let mut statement = self.connection.prepare(
+            "SELECT user_id, title, author, status, rating, date FROM books ORDER BY user_id, position",
+        )?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, Book {
+            title: row.get(1)?,
+            author: row.get(2)?,
+            status: ReadingStatus::from_code(&row.get::<_, String>(3)?).unwrap_or_default(),
+            rating: row.get(4)?,
+            date: row.get(5)?,
+        })))?;
+        for row in rows {
+            let (user_id, book) = row?;
+            users.entry(user_id as u64).or_default().books.push(book);
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT user_id, title, year, rating, date FROM movies ORDER BY user_id, position",
+        )?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, Movie {
+            title: row.get(1)?,
+            year: row.get(2)?,
+            rating: row.get(3)?,
+            date: row.get(4)?,
+        })))?;
+        for row in rows {
+            let (user_id, movie) = row?;
+            users.entry(user_id as u64).or_default().movies.push(movie);
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT user_id, text, title, author, date FROM quotes ORDER BY user_id, position",
+        )?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, Quote {
+            text: row.get(1)?,
+            title: row.get(2)?,
+            author: row.get(3)?,
+            date: row.get(4)?,
+        })))?;
+        for row in rows {
+            let (user_id, quote) = row?;
+            users.entry(user_id as u64).or_default().quotes.push(quote);
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT user_id, title, seasons, rating FROM series ORDER BY user_id, position",
+        )?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, Series {
+            title: row.get(1)?,
+            seasons: row.get(2)?,
+            rating: row.get(3)?,
+        })))?;
+        for row in rows {
+            let (user_id, series) = row?;
+            users.entry(user_id as u64).or_default().series.push(series);
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT user_id, title, year FROM watchlist ORDER BY user_id, position",
+        )?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, WatchlistItem {
+            title: row.get(1)?,
+            year: row.get(2)?,
+        })))?;
+        for row in rows {
+            let (user_id, item) = row?;
+            users.entry(user_id as u64).or_default().watchlist.push(item);
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT user_id, rating_scale, language, telegram_language FROM settings",
+        )?;
+        let rows = statement.query_map([], |row| {
+            let language = |index: usize| row.get::<_, Option<String>>(index)
+                .map(|code| code.as_deref().and_then(Language::from_code));
+            Ok((row.get::<_, i64>(0)?, Settings {
+                rating_scale: RatingScale::from_code(&row.get::<_, String>(1)?).unwrap_or_default(),
+                language: language(2)?,
+                telegram_language: language(3)?,
+            }))
+        })?;
+        for row in rows {
+            let (user_id, settings) = row?;
+            users.entry(user_id as u64).or_default().settings = settings;
+        }
+
+        Ok(users)
+    }
+}
+
+fn save_user(transaction: &Transaction, user_id: i64, data: &UserData) -> rusqlite::Result<()> {
+    for table in TABLES {
+        transaction.execute(&format!("DELETE FROM {} WHERE user_id = ?1", table), params![user_id])?;
+    }
+    for (position, book) in data.books.iter().enumerate() {
+        transaction.execute(
+            "INSERT INTO books (user_id, position, title, author, status, rating, date) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![user_id, position as i64, book.title, book.author, book.status.code(), book.rating, book.date],
+        )?;
+    }
+    for (position, movie) in data.movies.iter().enumerate() {
+        transaction.execute(
+            "INSERT INTO movies (user_id, position, title, year, rating, date) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![user_id, position as i64, movie.title, movie.year, movie.rating, movie.date],
+        )?;
+    }
+    for (position, quote) in data.quotes.iter().enumerate() {
+        transaction.execute(
+            "INSERT INTO quotes (user_id, position, text, title, author, date) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![user_id, position as i64, quote.text, quote.title, quote.author, quote.date],
+        )?;
+    }
+    for (position, series) in data.series.iter().enumerate() {
+        transaction.execute(
+            "INSERT INTO series (user_id, position, title, seasons, rating) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![user_id, position as i64, series.title, series.seasons, series.rating],
+        )?;
+    }
+    for (position, item) in data.watchlist.iter().enumerate() {
+        transaction.execute(
+            "INSERT INTO watchlist (user_id, position, title, year) VALUES (?1, ?2, ?3, ?4)",
+            params![user_id, position as i64, item.title, item.year],
+        )?;
+    }
+    let settings = &data.settings;
+    transaction.execute(
+        "INSERT INTO settings (user_id, rating_scale, language, telegram_language) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            user_id,
+            settings.rating_scale.code(),
+            settings.language.map(|language| language.code()),
+            settings.telegram_language.map(|language| language.code()),
+        ],
+    )?;
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    fn load(&mut self) -> Result<HashMap<u64, UserData>, String> {
+        self.load_users().map_err(sql_error)
+    }
+
+    /// Replaces every row of the user in one transaction, so a crash leaves the previous data.
+    fn save(&mut self, user_id: u64, data: &UserData) -> Result<(), String> {
+        let transaction = self.connection.transaction().map_err(sql_error)?;
+        save_user(&transaction, user_id as i64, data).map_err(sql_error)?;
+        transaction.commit().map_err(sql_error)
+    }
+}
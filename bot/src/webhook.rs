@@ -0,0 +1,44 @@
+use crate::bot::{allowed_updates, handle_update};
+use crate::config::config;
+use crate::domain::State;
+
+use frankenstein::Update;
+use telegram_api::*;
+
+fn webhook_secret() -> Result<&'static str, String> {
+    config().webhook_secret.as_deref().ok_or("WEBHOOK_SECRET is not set".to_string())
+}
+
+/// Compares in constant time, so the secret can't be guessed from response times.
+fn same_secret(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len()
+        && expected.bytes().zip(actual.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Checks the `X-Telegram-Bot-Api-Secret-Token` header of a webhook request.
+pub fn verify_secret_token(secret_token: Option<&str>) -> Result<(), String> {
+    let secret = webhook_secret()?;
+    match secret_token {
+        Some(token) if same_secret(secret, token) => Ok(()),
+        Some(_) => Err("Invalid secret token".to_string()),
+        None => Err("Missing secret token".to_string()),
+    }
+}
+
+/// Tells Telegram to deliver updates to `url` instead of `get_updates`.
+pub fn set_webhook_url(url: &str) -> Result<(), String> {
+    let secret = webhook_secret()?;
+    let api = config().telegram_api();
+    set_webhook(&api, url, secret, allowed_updates())
+        .map_err(|err| format!("Error setting webhook: {:?}", err))
+}
+
+/// Handles one update delivered to the webhook, `update` is the JSON body of the request.
+pub fn handle_webhook_update(state: &mut State, update: &str, secret_token: Option<&str>) -> Result<(), String> {
+    verify_secret_token(secret_token)?;
+    let update: Update = serde_json::from_str(update)
+        .map_err(|err| format!("Update JSON deserialization failed: {}", err))?;
+    let api = config().telegram_api();
+    handle_update(state, &api, &update);
+    Ok(())
+}
@@ -1,14 +1,38 @@
-use once_cell::sync::Lazy;
-use reqwest::StatusCode;
+use crate::config::config;
+
 use serde::Deserialize;
+use telegram_api::http::{self, StatusCode};
+use telegram_api::retry::{Retry, RetryPolicy};
 use uuid::Uuid;
 
-use std::env;
+// Checked when the configuration is loaded, the golem dialog runtime requires it
+fn golem_token() -> &'static str {
+    config().golem_token.as_deref().unwrap_or_default()
+}
 
-static GOLEM_TOKEN: Lazy<String> = Lazy::new(|| {
-    env::var("GOLEM_TOKEN").unwrap()
-});
-const API_ROOT: &str = "https://release.api.golem.cloud/v1";
+#[derive(Debug)]
+enum Failure {
+    // No response, e.g. the connection failed or timed out
+    Request(http::Error),
+    // Golem is overloaded or failing, the response is kept in case every attempt fails
+    Status(http::Response),
+}
+
+/// Sends a request to the Golem API, again after a failure that may be transient.
+/// Statuses other than 429 and 5xx are returned as they are, for the caller to check.
+fn send(name: &str, send: impl Fn() -> Result<http::Response, http::Error>) -> Result<http::Response, String> {
+    let result = RetryPolicy::default().run(name, |_: &Failure| Retry::WithBackoff, || match send() {
+        Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS || response.status().is_server_error() => {
+            Err(Failure::Status(response))
+        }
+        Ok(response) => Ok(response),
+        Err(err) => Err(Failure::Request(err)),
+    });
+    match result {
+        Ok(response) | Err(Failure::Status(response)) => Ok(response),
+        Err(Failure::Request(err)) => Err(format!("Request error: {}", err)),
+    }
+}
 
 #[derive(Deserialize, Debug)]
 pub struct FunctionResult<T> {
@@ -16,21 +40,23 @@ pub struct FunctionResult<T> {
 }
 
 pub fn create_worker(worker_id: Uuid, template: &str, env: Vec<Vec<&str>>) -> Result<(), String> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/templates/{}/workers", API_ROOT, template);
+    let client = http::Client::new();
+    let url = format!("{}/templates/{}/workers", config().golem_api_root, template);
     // example body: {"name":"46c2db15-f9d3-4a0c-9f12-ef3116391c8c","env":[],"args":[]}
     let body = serde_json::json!({
         "name": worker_id.to_string(),
         "env": env,
         "args": []
     });
-    let response = client.post(&url)
-        .json(&body)
-        .bearer_auth(GOLEM_TOKEN.as_str())
-        .send()
-        .map_err(|err| format!("Request error: {}", err))?;
+    let response = send("Create worker", || client.post(&url).json(&body).bearer_auth(golem_token()).send())?;
     println!("create worker response: {:?}", response);
 
+    // Worker IDs are new UUIDs, so an existing worker was created by an attempt whose response got lost
+    if response.status() == StatusCode::CONFLICT {
+        println!("Worker with ID {} already exists", worker_id);
+        return Ok(());
+    }
+
     // Check if the HTTP request was successful
     if response.status() != StatusCode::OK {
         return Err(format!("Create worker: Received non-OK HTTP status: {}", response.status()));
@@ -59,14 +85,11 @@ pub fn create_worker(worker_id: Uuid, template: &str, env: Vec<Vec<&str>>) -> Re
 }
 
 pub fn get_invocation_key(worker_id: Uuid, template: &str) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/templates/{}/workers/{}/key", API_ROOT, template, worker_id);
+    let client = http::Client::new();
+    let url = format!("{}/templates/{}/workers/{}/key", config().golem_api_root, template, worker_id);
 
     // Send request and handle request errors
-    let response = client.post(&url)
-        .bearer_auth(GOLEM_TOKEN.as_str())
-        .send()
-        .map_err(|err| format!("Request error: {}", err))?;
+    let response = send("Get invocation key", || client.post(&url).bearer_auth(golem_token()).send())?;
 
     // Parse JSON and handle JSON errors
     let json_value = response.json::<serde_json::Value>()
@@ -82,36 +105,34 @@ pub fn get_invocation_key(worker_id: Uuid, template: &str) -> Result<String, Str
 }
 
 pub fn delete_worker(template: &str, worker_id: Uuid) {
-    let client = reqwest::Client::new();
+    let client = http::Client::new();
     let url = format!(
         "{}/templates/{}/workers/{}",
-        API_ROOT,
+        config().golem_api_root,
         template,
         worker_id
     );
-    let response = client.delete(&url).bearer_auth(GOLEM_TOKEN.as_str()).send();
+    let response = send("Delete worker", || client.delete(&url).bearer_auth(golem_token()).send());
     println!("delete worker response: {:?}", response);
 }
 
-pub fn invoke_function(template: &str, worker_id: Uuid, invocation_key: String, function: &str, params: String) -> Result<serde_json::Value, String> {
-    let client = reqwest::Client::new();
+pub fn invoke_function(template: &str, worker_id: Uuid, invocation_key: String, function: &str, params: Vec<serde_json::Value>) -> Result<serde_json::Value, String> {
+    let client = http::Client::new();
     let url = format!(
         "{}/templates/{}/workers/{}/invoke-and-await?invocation-key={}&function={}",
-        API_ROOT,
+        config().golem_api_root,
         template,
         worker_id,
         invocation_key,
         function
     );
     let body = serde_json::json!({
-        "params": [params]
+        "params": params
     });
     println!("{:?}", body);
-    let response = client.post(&url)
-        .json(&body)
-        .bearer_auth(GOLEM_TOKEN.as_str())
-        .send()
-        .map_err(|err| format!("Invoke function: Request error: {}", err))?;
+    // Golem runs an invocation once per invocation key, so sending it again can't repeat a dialog step
+    let response = send("Invoke function", || client.post(&url).json(&body).bearer_auth(golem_token()).send())
+        .map_err(|err| format!("Invoke function: {}", err))?;
 
     // Check if the HTTP request was successful
     if response.status() != StatusCode::OK {
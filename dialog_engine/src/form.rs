@@ -0,0 +1,481 @@
+use crate::{BACK_COMMAND, Language, RatingScale, SKIP_COMMAND, kept_value, validate_date, validate_year, with_current_value};
+use crate::widgets::{Widget, WidgetAction, skip_row};
+
+use frankenstein::{CallbackQuery, InlineKeyboardMarkup, Update, UpdateContent};
+use serde::Serialize;
+use telegram_api::*;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+
+/// An answer to one field of a form.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Value {
+    Text(String),
+    Number(u32),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Number(number) => write!(f, "{}", number),
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(number: u32) -> Self {
+        Value::Number(number)
+    }
+}
+
+/// Answers by field name.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Values(BTreeMap<String, Value>);
+
+impl Values {
+    pub const fn new() -> Values {
+        Values(BTreeMap::new())
+    }
+
+    pub fn with(mut self, name: &str, value: impl Into<Value>) -> Values {
+        self.insert(name, value.into());
+        self
+    }
+
+    pub fn with_optional(self, name: &str, value: Option<impl Into<Value>>) -> Values {
+        match value {
+            Some(value) => self.with(name, value),
+            None => self,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    pub fn insert(&mut self, name: &str, value: Value) {
+        self.0.insert(name.to_string(), value);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn optional_text(&self, name: &str) -> Option<String> {
+        match self.get(name) {
+            Some(Value::Text(text)) => Some(text.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, name: &str) -> String {
+        self.optional_text(name).unwrap_or_default()
+    }
+
+    pub fn optional_number(&self, name: &str) -> Option<u32> {
+        match self.get(name) {
+            Some(Value::Number(number)) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self, name: &str) -> u32 {
+        self.optional_number(name).unwrap_or_default()
+    }
+}
+
+/// The settings of the user a dialog runs for, kept in its `FormState` from the start.
+/// They are fixed when the dialog is created: a dialog started before the user changes
+/// their rating scale or language keeps asking and answering in the old ones until it completes.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct DialogSettings {
+    pub language: Language,
+    pub rating_scale: RatingScale,
+    // The bot's token to send the dialog's messages with, left out of the state shown by `state`
+    #[serde(skip)]
+    pub telegram_token: Option<String>,
+}
+
+impl DialogSettings {
+    /// The settings a Golem worker was created with, in its `LANGUAGE`, `RATING_SCALE` and `TELEGRAM_TOKEN` environment variables.
+    /// Each worker runs a single dialog, so its environment belongs to the dialog's user.
+    pub fn from_env() -> DialogSettings {
+        let language = env::var("LANGUAGE").ok().and_then(|code| Language::from_code(&code));
+        let rating_scale = env::var("RATING_SCALE").ok().and_then(|code| RatingScale::from_code(&code));
+        DialogSettings {
+            language: language.unwrap_or_default(),
+            rating_scale: rating_scale.unwrap_or_default(),
+            telegram_token: env::var("TELEGRAM_TOKEN").ok(),
+        }
+    }
+
+    /// The Bot API the dialog sends its messages to.
+    pub fn api(&self) -> Result<Api, String> {
+        match &self.telegram_token {
+            Some(token) => Ok(Api::new(token)),
+            None => Err("The dialog was created without a Telegram token".to_string()),
+        }
+    }
+}
+
+pub type Parser = fn(&String, &DialogSettings) -> Result<Value, String>;
+pub type Validator = fn(&Value, &DialogSettings) -> Result<(), String>;
+pub type Condition = fn(&Values) -> bool;
+
+pub fn parse_text(text: &String, _settings: &DialogSettings) -> Result<Value, String> {
+    Ok(Value::Text(text.clone()))
+}
+
+pub fn parse_number(text: &String, settings: &DialogSettings) -> Result<Value, String> {
+    text.trim().parse::<u32>()
+        .map(Value::Number)
+        .map_err(|_| t!(settings.language, "form.number"))
+}
+
+/// Parses a rating on the user's scale into its score.
+pub fn parse_rating(text: &String, settings: &DialogSettings) -> Result<Value, String> {
+    settings.rating_scale.parse(settings.language, text).map(Value::Number)
+}
+
+pub fn display_rating(value: &Value, settings: &DialogSettings) -> String {
+    match value {
+        Value::Number(score) => settings.rating_scale.format(*score),
+        value => value.to_string(),
+    }
+}
+
+pub fn parse_year(text: &String, settings: &DialogSettings) -> Result<Value, String> {
+    validate_year(text).map(Value::Number).map_err(|key| t!(settings.language, key))
+}
+
+pub fn parse_date(text: &String, settings: &DialogSettings) -> Result<Value, String> {
+    validate_date(text).map(Value::Text).map_err(|key| t!(settings.language, key))
+}
+
+/// One question of a form: the answer is parsed, then checked by every validator in order.
+pub struct Field {
+    pub name: &'static str,
+    pub prompt: String,
+    pub parser: Parser,
+    pub validators: Vec<Validator>,
+    // The field is only asked if the condition holds for the earlier answers
+    pub condition: Option<Condition>,
+    // Optional fields can be left empty with `/skip` or the Skip button
+    pub optional: bool,
+    pub widget: Option<Widget>,
+    // Shows the current answer in the prompt, `Value`'s `Display` if not set
+    pub display: Option<fn(&Value, &DialogSettings) -> String>,
+}
+
+impl Field {
+    pub fn new(name: &'static str, prompt: &str, parser: Parser) -> Field {
+        Field {
+            name,
+            prompt: prompt.to_string(),
+            parser,
+            validators: vec![],
+            condition: None,
+            optional: false,
+            widget: None,
+            display: None,
+        }
+    }
+
+    pub fn text(name: &'static str, prompt: &str) -> Field {
+        Field::new(name, prompt, parse_text)
+    }
+
+    /// A rating on the user's scale, stored as a score.
+    pub fn rating(name: &'static str, settings: &DialogSettings) -> Field {
+        let scale = settings.rating_scale;
+        let prompt = t!(settings.language, "rating.prompt", hint = scale.hint());
+        let mut field = Field::new(name, &prompt, parse_rating)
+            .widget(Widget::Rating(scale));
+        field.display = Some(display_rating);
+        field
+    }
+
+    pub fn validate(mut self, validator: Validator) -> Field {
+        self.validators.push(validator);
+        self
+    }
+
+    pub fn when(mut self, condition: Condition) -> Field {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn optional(mut self) -> Field {
+        self.optional = true;
+        self
+    }
+
+    pub fn widget(mut self, widget: Widget) -> Field {
+        self.widget = Some(widget);
+        self
+    }
+
+    fn prompt(&self, language: Language) -> String {
+        if self.optional {
+            t!(language, "form.skip_hint", prompt = self.prompt, command = SKIP_COMMAND)
+        } else {
+            self.prompt.clone()
+        }
+    }
+
+    fn display(&self, value: &Value, settings: &DialogSettings) -> String {
+        match self.display {
+            Some(display) => display(value, settings),
+            None => value.to_string(),
+        }
+    }
+
+    fn applies(&self, values: &Values) -> bool {
+        self.condition.map_or(true, |condition| condition(values))
+    }
+
+    fn parse(&self, text: &String, settings: &DialogSettings) -> Result<Value, String> {
+        let value = (self.parser)(text, settings)?;
+        for validator in &self.validators {
+            validator(&value, settings)?;
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum FormStep {
+    Started,
+    // Index of the field being asked
+    Field(usize),
+    Completed,
+}
+
+/// The persistent part of a form dialog, kept in the worker's global state.
+#[derive(Debug, Serialize)]
+pub struct FormState {
+    pub settings: DialogSettings,
+    pub step: FormStep,
+    pub values: Values,
+    // Answers of the entry being edited, if the dialog was started with `edit`
+    pub original: Option<Values>,
+}
+
+impl FormState {
+    pub const fn new(settings: DialogSettings) -> FormState {
+        FormState {
+            settings,
+            step: FormStep::Started,
+            values: Values::new(),
+            original: None,
+        }
+    }
+}
+
+/// A dialog declared as an ordered list of fields.
+/// The engine asks the fields one by one and builds the result record from the answers.
+pub struct Form<T> {
+    pub fields: Vec<Field>,
+    pub build: fn(&Values) -> T,
+    // Confirmation of a new entry, see `confirmation`
+    pub added: fn(&T, &DialogSettings) -> String,
+    // Confirmation of an edited entry, see `confirmation`
+    pub updated: fn(&T, &DialogSettings) -> String,
+}
+
+impl<T> Form<T> {
+    /// Handles a message or a widget button of the user, returns the result once every field is answered.
+    pub fn handle_update(&self, state: &mut FormState, api: &Api, update: &Update) -> Result<Option<T>, String> {
+        println!("Dialog state: {:?}", state.step);
+        match update.content {
+            UpdateContent::Message(ref message) => {
+                let chat_id = message.chat.id;
+                match state.step {
+                    FormStep::Started => self.advance(state, api, chat_id, 0),
+                    FormStep::Field(index) => {
+                        if let Some(ref text) = message.text {
+                            self.handle_text(state, api, chat_id, index, text);
+                        }
+                    }
+                    FormStep::Completed => (),
+                }
+            }
+            UpdateContent::CallbackQuery(ref callback_query) => self.handle_callback_query(state, api, callback_query),
+            _ => (),
+        }
+        Ok(self.result(state))
+    }
+
+    fn handle_text(&self, state: &mut FormState, api: &Api, chat_id: i64, index: usize, text: &String) {
+        if text.trim() == BACK_COMMAND {
+            self.back(state, api, chat_id, index);
+        } else if text.trim() == SKIP_COMMAND {
+            self.skip(state, api, chat_id, index);
+        } else {
+            match self.answer(state, index, text) {
+                Ok(()) => self.advance(state, api, chat_id, index + 1),
+                Err(err) => send_message(api, chat_id, &err),
+            }
+        }
+    }
+
+    /// Every callback query is answered, so Telegram stops showing the button as loading.
+    fn handle_callback_query(&self, state: &mut FormState, api: &Api, callback_query: &CallbackQuery) {
+        let chat_id = callback_query.message.as_ref().map_or(callback_query.from.id as i64, |message| message.chat.id);
+        let current = match state.step {
+            FormStep::Field(index) => Some(index),
+            _ => None,
+        };
+        match callback_query.data.as_deref().and_then(WidgetAction::parse) {
+            Some(WidgetAction::Pick(field, text)) if Some(field) == current => {
+                answer_callback_query(api, &callback_query.id, None);
+                self.handle_text(state, api, chat_id, field, &text);
+            }
+            Some(WidgetAction::Skip(field)) if Some(field) == current => {
+                answer_callback_query(api, &callback_query.id, None);
+                self.skip(state, api, chat_id, field);
+            }
+            Some(WidgetAction::Navigate(field, view)) if Some(field) == current => {
+                answer_callback_query(api, &callback_query.id, None);
+                let view = (self.prompt(state), self.keyboard(state, Some(&view)));
+                if let (Some(message), (Some(prompt), Some(keyboard))) = (&callback_query.message, view) {
+                    edit_message_text(api, chat_id, message.message_id, &prompt, Some(keyboard));
+                }
+            }
+            Some(WidgetAction::Ignore) => answer_callback_query(api, &callback_query.id, None),
+            _ => answer_callback_query(api, &callback_query.id, Some(&t!(state.settings.language, "form.step_over"))),
+        }
+    }
+
+    /// Starts a dialog that changes an existing entry, its answers can be kept with `/keep`.
+    pub fn edit(&self, state: &mut FormState, api: &Api, chat_id: i64, original: Values) {
+        state.values = original.clone();
+        state.original = Some(original);
+        self.advance(state, api, chat_id, 0);
+    }
+
+    /// Starts a dialog with some answers already known, asking only the remaining fields.
+    pub fn prefill(&self, state: &mut FormState, api: &Api, chat_id: i64, values: Values) {
+        state.values = values;
+        let next = self.fields.iter()
+            .position(|field| field.applies(&state.values) && !state.values.contains(field.name));
+        state.step = next.map_or(FormStep::Completed, FormStep::Field);
+        self.send_prompt(state, api, chat_id);
+    }
+
+    pub fn result(&self, state: &FormState) -> Option<T> {
+        match state.step {
+            FormStep::Completed => Some((self.build)(&state.values)),
+            _ => None,
+        }
+    }
+
+    /// The confirmation of a completed entry. The form doesn't send it, the caller does
+    /// once the entry is saved, as it may first have to ask what to do with a duplicate.
+    pub fn confirmation(&self, state: &FormState) -> Option<String> {
+        self.result(state).map(|result| match state.original {
+            Some(_) => (self.updated)(&result, &state.settings),
+            None => (self.added)(&result, &state.settings),
+        })
+    }
+
+    fn answer(&self, state: &mut FormState, index: usize, text: &String) -> Result<(), String> {
+        let field = &self.fields[index];
+        let value = match kept_value(text, state.values.get(field.name)) {
+            Some(value) => value,
+            None => field.parse(text, &state.settings)?,
+        };
+        state.values.insert(field.name, value);
+        Ok(())
+    }
+
+    fn skip(&self, state: &mut FormState, api: &Api, chat_id: i64, index: usize) {
+        let field = &self.fields[index];
+        if field.optional {
+            state.values.remove(field.name);
+            self.advance(state, api, chat_id, index + 1);
+        } else {
+            send_message(api, chat_id, &t!(state.settings.language, "form.cannot_skip"));
+        }
+    }
+
+    /// Returns to the previous field that was asked, keeping every answer given so far.
+    fn back(&self, state: &mut FormState, api: &Api, chat_id: i64, index: usize) {
+        match (0..index).rev().find(|previous| self.fields[*previous].applies(&state.values)) {
+            Some(previous) => {
+                state.step = FormStep::Field(previous);
+                self.send_prompt(state, api, chat_id);
+            }
+            None => send_message(api, chat_id, &t!(state.settings.language, "form.first_step")),
+        }
+    }
+
+    /// Moves to the first field from `from` that applies to the answers so far.
+    fn advance(&self, state: &mut FormState, api: &Api, chat_id: i64, from: usize) {
+        let next = (from..self.fields.len()).find(|index| self.fields[*index].applies(&state.values));
+        // Answers of skipped fields are dropped, e.g. the rating of a book that is no longer finished
+        for field in &self.fields[from..next.unwrap_or(self.fields.len())] {
+            state.values.remove(field.name);
+        }
+        state.step = next.map_or(FormStep::Completed, FormStep::Field);
+        self.send_prompt(state, api, chat_id);
+    }
+
+    fn prompt(&self, state: &FormState) -> Option<String> {
+        match state.step {
+            FormStep::Started | FormStep::Completed => None,
+            FormStep::Field(index) => {
+                // Fields of an edited entry, or answered before going back, show the answer to keep
+                let field = &self.fields[index];
+                let language = state.settings.language;
+                let current = state.values.get(field.name).map(|value| field.display(value, &state.settings));
+                Some(with_current_value(language, field.prompt(language), current))
+            }
+        }
+    }
+
+    /// The widget of the current field, showing `view` or the first view, with a Skip button for optional fields.
+    fn keyboard(&self, state: &FormState, view: Option<&str>) -> Option<InlineKeyboardMarkup> {
+        let index = match state.step {
+            FormStep::Field(index) => index,
+            _ => return None,
+        };
+        let field = &self.fields[index];
+        let language = state.settings.language;
+        let mut rows = match (field.widget, view) {
+            (Some(widget), Some(view)) => widget.view(index, view, language)?,
+            (Some(widget), None) => widget.keyboard(index, language),
+            (None, _) => vec![],
+        };
+        if field.optional {
+            rows.push(skip_row(index, language));
+        }
+        if rows.is_empty() {
+            None
+        } else {
+            Some(InlineKeyboardMarkup::builder().inline_keyboard(rows).build())
+        }
+    }
+
+    fn send_prompt(&self, state: &FormState, api: &Api, chat_id: i64) {
+        if let Some(message) = self.prompt(state) {
+            match self.keyboard(state, None) {
+                Some(keyboard) => send_message_with_keyboard(api, chat_id, &message, keyboard),
+                None => send_message(api, chat_id, &message),
+            }
+        }
+    }
+}
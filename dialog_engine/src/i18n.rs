@@ -0,0 +1,96 @@
+mod en;
+mod ru;
+
+use serde::{Deserialize, Serialize};
+
+/// Language of the messages sent to a user.
+/// Messages are looked up by key in the catalog of the language, falling back to English.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Language {
+    #[default]
+    English,
+    Russian,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Russian];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Russian => "ru",
+        }
+    }
+
+    /// Accepts an IETF language tag such as Telegram's `language_code`, so `ru-RU` is Russian.
+    pub fn from_code(code: &str) -> Option<Language> {
+        let primary = code.split(|c| c == '-' || c == '_').next().unwrap_or_default().to_lowercase();
+        Language::ALL.into_iter().find(|language| language.code() == primary)
+    }
+
+    /// The name of the language in the language itself.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Russian => "Русский",
+        }
+    }
+
+    fn messages(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => en::MESSAGES,
+            Language::Russian => ru::MESSAGES,
+        }
+    }
+
+    /// The message with the given key, or the key itself if no catalog has it.
+    pub fn text<'a>(&self, key: &'a str) -> &'a str {
+        let find = |language: Language| language.messages().iter()
+            .find(|(message_key, _)| *message_key == key)
+            .map(|(_, text)| *text);
+        match find(*self).or_else(|| find(Language::English)) {
+            Some(text) => text,
+            None => key,
+        }
+    }
+
+    /// The message with the given key, with every `{name}` placeholder replaced by its argument.
+    pub fn format(&self, key: &str, args: &[(&str, String)]) -> String {
+        let mut text = self.text(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), value);
+        }
+        text
+    }
+
+    /// The message for `count` items, e.g. `book.count` looks up `book.count.one`, `book.count.few` or `book.count.many`.
+    pub fn count(&self, key: &str, count: usize) -> String {
+        self.format(&format!("{}.{}", key, self.plural_category(count)), &[("count", count.to_string())])
+    }
+
+    fn plural_category(&self, count: usize) -> &'static str {
+        match self {
+            Language::English if count == 1 => "one",
+            Language::English => "many",
+            // 1, 21, 31… книга; 2-4, 22-24… книги; 0, 5-20, 25-30… книг
+            Language::Russian => match (count % 10, count % 100) {
+                (1, rest) if rest != 11 => "one",
+                (2..=4, rest) if !(12..=14).contains(&rest) => "few",
+                _ => "many",
+            },
+        }
+    }
+}
+
+/// Looks up a message, replacing placeholders by the named arguments:
+/// `t!(language, "book.added", title = book.title, author = book.author)`.
+#[macro_export]
+macro_rules! t {
+    ($language:expr, $key:expr) => {
+        $language.text($key).to_string()
+    };
+    ($language:expr, $key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $language.format($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
@@ -0,0 +1,199 @@
+/// English messages, the fallback for keys missing from other catalogs.
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Forms
+    ("form.current_value", "{prompt} (current: {value}, send {command} to keep it)"),
+    ("form.skip_hint", "{prompt} (send {command} to skip)"),
+    ("form.cannot_skip", "This step can't be skipped"),
+    ("form.first_step", "This is the first step, send /reset to cancel"),
+    ("form.step_over", "This step is over"),
+    ("form.number", "Must be a number"),
+    ("error.year_range", "Year must be between 1900 and 2100"),
+    ("error.year_number", "Year must be a number"),
+    ("error.date_format", "Date must be today, yesterday, YYYY-MM-DD or DD.MM.YYYY"),
+    ("error.date_ambiguous", "Write the year first, YYYY-MM-DD, or the day first with dots, DD.MM.YYYY"),
+    ("error.no_such_date", "There is no such date"),
+    ("error.future_date", "The date can't be in the future"),
+    ("date.hint", "today, yesterday or YYYY-MM-DD"),
+
+    // Widgets
+    ("widget.skip", "Skip"),
+    ("widget.today", "Today"),
+    ("widget.yesterday", "Yesterday"),
+    ("widget.decade", "{decade}s"),
+    ("widget.decades", "« Decades"),
+    ("widget.month", "{month} {year}"),
+    ("month.1", "January"),
+    ("month.2", "February"),
+    ("month.3", "March"),
+    ("month.4", "April"),
+    ("month.5", "May"),
+    ("month.6", "June"),
+    ("month.7", "July"),
+    ("month.8", "August"),
+    ("month.9", "September"),
+    ("month.10", "October"),
+    ("month.11", "November"),
+    ("month.12", "December"),
+    ("weekday.1", "Mo"),
+    ("weekday.2", "Tu"),
+    ("weekday.3", "We"),
+    ("weekday.4", "Th"),
+    ("weekday.5", "Fr"),
+    ("weekday.6", "Sa"),
+    ("weekday.7", "Su"),
+
+    // Ratings
+    ("rating.prompt", "Enter rating ({hint})"),
+    ("rating.number", "Rating must be a number"),
+    ("rating.range.5", "Rating must be between 1 and 5"),
+    ("rating.range.half", "Rating must be between 0.5 and 5 in steps of 0.5"),
+    ("rating.range.10", "Rating must be between 1 and 10"),
+    ("rating.name.5", "1 to 5 stars"),
+    ("rating.name.half", "0.5 to 5 stars in half stars"),
+    ("rating.name.10", "1 to 10 points"),
+
+    // Shelves
+    ("shelf.want", "want to read"),
+    ("shelf.reading", "currently reading"),
+    ("shelf.finished", "finished"),
+    ("shelf.abandoned", "abandoned"),
+
+    // Dialogs
+    ("prompt.title", "Enter title"),
+    ("prompt.author", "Enter author"),
+    ("prompt.year", "Enter year"),
+    ("book.status", "Which shelf? 1 - want to read, 2 - currently reading, 3 - finished, 4 - abandoned"),
+    ("book.status_error", "Send a number from 1 to 4"),
+    ("book.date", "When did you finish it? ({hint})"),
+    ("book.added.rated_on", "Added book {title} by {author} with rating {rating}, read on {date}"),
+    ("book.added.rated", "Added book {title} by {author} with rating {rating}"),
+    ("book.added.shelved", "Added book {title} by {author} to {shelf}"),
+    ("book.updated.rated_on", "Updated book {title} by {author} with rating {rating}, read on {date}"),
+    ("book.updated.rated", "Updated book {title} by {author} with rating {rating}"),
+    ("book.updated.shelved", "Updated book {title} by {author}, now {shelf}"),
+    ("movie.date", "When did you watch it? ({hint})"),
+    ("movie.with_rating", " with rating {rating}"),
+    ("movie.watched_on", ", watched on {date}"),
+    ("movie.added", "Added movie {movie}"),
+    ("movie.updated", "Updated movie {movie}"),
+    ("quote.text", "Enter text"),
+    ("quote.source", "\"{text}\" from {title}"),
+    ("quote.source_by", "\"{text}\" from {title} by {author}"),
+    ("quote.added", "Added quote: {quote}"),
+    ("quote.updated", "Updated quote: {quote}"),
+    ("series.seasons", "How many seasons have you watched?"),
+    ("series.seasons_error", "Number of seasons must be between 1 and 100"),
+    ("series.seasons_watched.one", "{count} season watched"),
+    ("series.seasons_watched.many", "{count} seasons watched"),
+    ("series.added", "Added series {title} ({seasons}) with rating {rating}"),
+    ("series.updated", "Updated series {title} ({seasons}) with rating {rating}"),
+
+    // Bot
+    ("help", "Use /add_book, /add_movie, /add_series or /add_quote to add a new item. Use /books, /movies, /series or /quotes to list your items. Use /edit_book, /edit_movie, /edit_series or /edit_quote to change an item. Use /books want, /books reading, /books finished or /books abandoned to see a single shelf and /shelve to move a book to another shelf. Use /watchlist <title> to save a movie for later and /watchlist to see the list. While adding an item, send /back to change the previous answer, /skip to leave an optional answer empty or /reset to start over. Use the buttons under a list to delete an item. Use /search to find an item and /stats to see your statistics. Use /scale to rate on 5 stars, half stars or 10 points and /language to change the language. Use /export to download your library or send a Goodreads or Letterboxd CSV export to import it."),
+    ("dialog.reset", "Dialog reset"),
+    ("dialog.unavailable", "This dialog isn't set up on this bot yet"),
+    ("item.gone", "This item no longer exists"),
+    ("library.empty", "Your library is empty"),
+    ("count.book.one", "{count} book"),
+    ("count.book.many", "{count} books"),
+    ("count.movie.one", "{count} movie"),
+    ("count.movie.many", "{count} movies"),
+    ("count.season.one", "{count} season"),
+    ("count.season.many", "{count} seasons"),
+    ("count.series.one", "{count} series"),
+    ("count.series.many", "{count} series"),
+    ("count.quote.one", "{count} quote"),
+    ("count.quote.many", "{count} quotes"),
+    ("edit.choose.book", "Choose a book to edit:"),
+    ("edit.choose.movie", "Choose a movie to edit:"),
+    ("edit.choose.series", "Choose a series to edit:"),
+    ("edit.choose.quote", "Choose a quote to edit:"),
+    ("delete.confirm", "Delete {item}?"),
+    ("delete.yes", "Yes, delete"),
+    ("delete.no", "No"),
+    ("delete.done", "Deleted"),
+    ("delete.deleted", "Deleted {item}"),
+    ("delete.cancelled", "Deletion cancelled"),
+    ("shelf.choose_book", "Choose a book to move to another shelf:"),
+    ("shelf.move_to", "Move {item} to:"),
+    ("shelf.moved", "Moved"),
+    ("shelf.moved_to", "Moved {item} to {shelf}"),
+    ("shelf.moved_unrated", "Moved {item} to {shelf}. Use /edit_book to rate it"),
+    ("books.usage", "Usage: /books [want|reading|finished|abandoned]"),
+    ("list.title.book", "Your books"),
+    ("list.title.movie", "Your movies"),
+    ("list.title.series", "Your series"),
+    ("list.title.quote", "Your quotes"),
+    ("list.empty.book", "You have no books"),
+    ("list.empty.movie", "You have no movies"),
+    ("list.empty.series", "You have no series"),
+    ("list.empty.quote", "You have no quotes"),
+    ("list.empty_shelf", "You have no books on the {shelf} shelf"),
+    ("list.page", "{title} (page {page}/{pages}):\n"),
+    ("list.more", "…and {count} more\n"),
+    ("list.delete", "Delete {item}"),
+    ("list.previous", "« Prev"),
+    ("list.next", "Next »"),
+    ("list.book.rated_on", "{title} by {author} (rating: {rating}, read on {date})"),
+    ("list.book.rated", "{title} by {author} (rating: {rating})"),
+    ("list.book.shelved", "{title} by {author} ({shelf})"),
+    ("list.movie.rated_on", "{movie} (rating: {rating}, watched on {date})"),
+    ("list.movie.rated", "{movie} (rating: {rating})"),
+    ("list.series", "{title} ({seasons}) (rating: {rating})"),
+    ("list.quote.saved_on", "{quote} (saved on {date})"),
+    ("watchlist.title", "Your watchlist"),
+    ("watchlist.empty", "Your watchlist is empty"),
+    ("watchlist.empty_hint", "Your watchlist is empty, add a movie with /watchlist <title> (<year>)"),
+    ("watchlist.added", "Added {item} to your watchlist"),
+    ("watchlist.already", "{item} is already on your watchlist"),
+    ("watchlist.title_required", "Title is required"),
+    ("watchlist.watched", "Watched {item}"),
+    ("watchlist.remove", "Remove"),
+    ("watchlist.removed", "Removed"),
+    ("duplicate.question", "{item} is already in your library. Keep both, replace the old one or discard the new one?"),
+    ("duplicate.keep", "Keep both"),
+    ("duplicate.replace", "Replace"),
+    ("duplicate.discard", "Discard"),
+    ("duplicate.kept", "Kept both copies of {item}"),
+    ("duplicate.replaced", "Replaced {old} with {new}"),
+    ("duplicate.gone", "The old entry no longer exists, added {item}"),
+    ("duplicate.discarded", "Discarded the new {item}"),
+    ("duplicate.answered", "This question was already answered"),
+    ("duplicate.unanswered", "The question about {item} wasn't answered, kept both copies"),
+    ("scale.choose", "Your ratings are shown as {scale}. Choose a rating scale:"),
+    ("scale.changed", "Ratings are now shown as {scale}"),
+    ("language.choose", "The current language is {language}. Choose a language:"),
+    ("language.changed", "Messages are now in English"),
+    ("search.usage", "Usage: /search <query>"),
+    ("search.nothing", "Nothing found for \"{query}\""),
+    ("search.results", "Search results for \"{query}\":\n{results}"),
+    ("search.header.book", "Books"),
+    ("search.header.movie", "Movies"),
+    ("search.header.series", "Series"),
+    ("search.header.quote", "Quotes"),
+    ("stats.empty", "Your library is empty, add something with /add_book, /add_movie, /add_series or /add_quote"),
+    ("stats.library", "Your library: {books}, {movies}, {series}, {quotes}\n"),
+    ("stats.shelves", "Books: {shelves}\n"),
+    ("stats.by_year", "\nBy year:\n"),
+    ("stats.no_date", "No date"),
+    ("stats.average", "\n{ratings}: average {average}\n"),
+    ("stats.book_ratings", "Book ratings"),
+    ("stats.movie_ratings", "Movie ratings"),
+    ("stats.series_ratings", "Series ratings"),
+    ("stats.authors", "\nMost read authors:\n"),
+    ("stats.sources", "\nMost quoted sources:\n"),
+    ("export.choose", "Choose the export format:"),
+    ("export.caption", "Your library"),
+    ("export.failed", "Export failed, please try again later"),
+    ("config.title", "Configuration:"),
+    ("import.not_csv", "Send a Goodreads or Letterboxd CSV export to import your library"),
+    ("import.too_large", "The file is too large, files up to {size} MB can be imported"),
+    ("import.download_failed", "Failed to download the file, please try again later"),
+    ("import.unknown", "This doesn't look like a Goodreads or Letterboxd export"),
+    ("import.summary", "Imported {items} from {source}. Skipped {skipped} unrated rows and {duplicates} duplicates."),
+    ("import.errors", "\n\n{count} rows could not be parsed:\n"),
+    ("import.line", "line {line}: {error}"),
+    ("import.line_title", "line {line} ({title}): {error}"),
+    ("import.title_missing", "title is missing"),
+    ("import.title_author_required", "\"{title}\": title and author are required"),
+];
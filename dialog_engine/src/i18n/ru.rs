@@ -0,0 +1,204 @@
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Forms
+    ("form.current_value", "{prompt} (сейчас: {value}, отправьте {command}, чтобы оставить)"),
+    ("form.skip_hint", "{prompt} (отправьте {command}, чтобы пропустить)"),
+    ("form.cannot_skip", "Этот шаг нельзя пропустить"),
+    ("form.first_step", "Это первый шаг, отправьте /reset, чтобы отменить"),
+    ("form.step_over", "Этот шаг уже пройден"),
+    ("form.number", "Нужно ввести число"),
+    ("error.year_range", "Год должен быть от 1900 до 2100"),
+    ("error.year_number", "Год должен быть числом"),
+    ("error.date_format", "Дата должна быть «сегодня», «вчера», ГГГГ-ММ-ДД или ДД.ММ.ГГГГ"),
+    ("error.date_ambiguous", "Напишите сначала год, ГГГГ-ММ-ДД, или день через точки, ДД.ММ.ГГГГ"),
+    ("error.no_such_date", "Такой даты не существует"),
+    ("error.future_date", "Дата не может быть в будущем"),
+    ("date.hint", "сегодня, вчера или ГГГГ-ММ-ДД"),
+
+    // Widgets
+    ("widget.skip", "Пропустить"),
+    ("widget.today", "Сегодня"),
+    ("widget.yesterday", "Вчера"),
+    ("widget.decade", "{decade}-е"),
+    ("widget.decades", "« Десятилетия"),
+    ("widget.month", "{month} {year}"),
+    ("month.1", "Январь"),
+    ("month.2", "Февраль"),
+    ("month.3", "Март"),
+    ("month.4", "Апрель"),
+    ("month.5", "Май"),
+    ("month.6", "Июнь"),
+    ("month.7", "Июль"),
+    ("month.8", "Август"),
+    ("month.9", "Сентябрь"),
+    ("month.10", "Октябрь"),
+    ("month.11", "Ноябрь"),
+    ("month.12", "Декабрь"),
+    ("weekday.1", "Пн"),
+    ("weekday.2", "Вт"),
+    ("weekday.3", "Ср"),
+    ("weekday.4", "Чт"),
+    ("weekday.5", "Пт"),
+    ("weekday.6", "Сб"),
+    ("weekday.7", "Вс"),
+
+    // Ratings
+    ("rating.prompt", "Введите оценку ({hint})"),
+    ("rating.number", "Оценка должна быть числом"),
+    ("rating.range.5", "Оценка должна быть от 1 до 5"),
+    ("rating.range.half", "Оценка должна быть от 0.5 до 5 с шагом 0.5"),
+    ("rating.range.10", "Оценка должна быть от 1 до 10"),
+    ("rating.name.5", "от 1 до 5 звёзд"),
+    ("rating.name.half", "от 0.5 до 5 звёзд с половинками"),
+    ("rating.name.10", "от 1 до 10 баллов"),
+
+    // Shelves
+    ("shelf.want", "хочу прочитать"),
+    ("shelf.reading", "читаю сейчас"),
+    ("shelf.finished", "прочитано"),
+    ("shelf.abandoned", "брошено"),
+
+    // Dialogs
+    ("prompt.title", "Введите название"),
+    ("prompt.author", "Введите автора"),
+    ("prompt.year", "Введите год"),
+    ("book.status", "Какая полка? 1 - хочу прочитать, 2 - читаю сейчас, 3 - прочитано, 4 - брошено"),
+    ("book.status_error", "Отправьте число от 1 до 4"),
+    ("book.date", "Когда вы её дочитали? ({hint})"),
+    ("book.added.rated_on", "Добавлена книга {title}, автор {author}, оценка {rating}, прочитана {date}"),
+    ("book.added.rated", "Добавлена книга {title}, автор {author}, оценка {rating}"),
+    ("book.added.shelved", "Книга {title}, автор {author}, добавлена на полку «{shelf}»"),
+    ("book.updated.rated_on", "Обновлена книга {title}, автор {author}, оценка {rating}, прочитана {date}"),
+    ("book.updated.rated", "Обновлена книга {title}, автор {author}, оценка {rating}"),
+    ("book.updated.shelved", "Обновлена книга {title}, автор {author}, полка «{shelf}»"),
+    ("movie.date", "Когда вы его посмотрели? ({hint})"),
+    ("movie.with_rating", ", оценка {rating}"),
+    ("movie.watched_on", ", просмотрен {date}"),
+    ("movie.added", "Добавлен фильм {movie}"),
+    ("movie.updated", "Обновлён фильм {movie}"),
+    ("quote.text", "Введите текст"),
+    ("quote.source", "«{text}» из {title}"),
+    ("quote.source_by", "«{text}» из {title}, автор {author}"),
+    ("quote.added", "Добавлена цитата: {quote}"),
+    ("quote.updated", "Обновлена цитата: {quote}"),
+    ("series.seasons", "Сколько сезонов вы посмотрели?"),
+    ("series.seasons_error", "Число сезонов должно быть от 1 до 100"),
+    ("series.seasons_watched.one", "просмотрен {count} сезон"),
+    ("series.seasons_watched.few", "просмотрено {count} сезона"),
+    ("series.seasons_watched.many", "просмотрено {count} сезонов"),
+    ("series.added", "Добавлен сериал {title} ({seasons}), оценка {rating}"),
+    ("series.updated", "Обновлён сериал {title} ({seasons}), оценка {rating}"),
+
+    // Bot
+    ("help", "Используйте /add_book, /add_movie, /add_series или /add_quote, чтобы добавить запись. Используйте /books, /movies, /series или /quotes, чтобы увидеть свои записи. Используйте /edit_book, /edit_movie, /edit_series или /edit_quote, чтобы изменить запись. Используйте /books want, /books reading, /books finished или /books abandoned, чтобы увидеть одну полку, и /shelve, чтобы переставить книгу на другую полку. Используйте /watchlist <название>, чтобы отложить фильм на потом, и /watchlist, чтобы увидеть список. Во время добавления отправьте /back, чтобы изменить предыдущий ответ, /skip, чтобы оставить необязательный ответ пустым, или /reset, чтобы начать заново. Кнопки под списком удаляют записи. Используйте /search для поиска и /stats для статистики. Используйте /scale, чтобы ставить оценки в 5 звёзд, с половинками или по 10 баллов, и /language, чтобы сменить язык. Используйте /export, чтобы скачать библиотеку, или отправьте CSV-экспорт из Goodreads или Letterboxd, чтобы импортировать его."),
+    ("dialog.reset", "Диалог сброшен"),
+    ("dialog.unavailable", "Этот диалог пока не настроен в боте"),
+    ("item.gone", "Этой записи больше нет"),
+    ("library.empty", "Ваша библиотека пуста"),
+    ("count.book.one", "{count} книга"),
+    ("count.book.few", "{count} книги"),
+    ("count.book.many", "{count} книг"),
+    ("count.movie.one", "{count} фильм"),
+    ("count.movie.few", "{count} фильма"),
+    ("count.movie.many", "{count} фильмов"),
+    ("count.season.one", "{count} сезон"),
+    ("count.season.few", "{count} сезона"),
+    ("count.season.many", "{count} сезонов"),
+    ("count.series.one", "{count} сериал"),
+    ("count.series.few", "{count} сериала"),
+    ("count.series.many", "{count} сериалов"),
+    ("count.quote.one", "{count} цитата"),
+    ("count.quote.few", "{count} цитаты"),
+    ("count.quote.many", "{count} цитат"),
+    ("edit.choose.book", "Выберите книгу для изменения:"),
+    ("edit.choose.movie", "Выберите фильм для изменения:"),
+    ("edit.choose.series", "Выберите сериал для изменения:"),
+    ("edit.choose.quote", "Выберите цитату для изменения:"),
+    ("delete.confirm", "Удалить {item}?"),
+    ("delete.yes", "Да, удалить"),
+    ("delete.no", "Нет"),
+    ("delete.done", "Удалено"),
+    ("delete.deleted", "Удалено: {item}"),
+    ("delete.cancelled", "Удаление отменено"),
+    ("shelf.choose_book", "Выберите книгу, чтобы переставить её на другую полку:"),
+    ("shelf.move_to", "Переставить {item} на полку:"),
+    ("shelf.moved", "Переставлено"),
+    ("shelf.moved_to", "{item} теперь на полке «{shelf}»"),
+    ("shelf.moved_unrated", "{item} теперь на полке «{shelf}». Используйте /edit_book, чтобы поставить оценку"),
+    ("books.usage", "Использование: /books [want|reading|finished|abandoned]"),
+    ("list.title.book", "Ваши книги"),
+    ("list.title.movie", "Ваши фильмы"),
+    ("list.title.series", "Ваши сериалы"),
+    ("list.title.quote", "Ваши цитаты"),
+    ("list.empty.book", "У вас нет книг"),
+    ("list.empty.movie", "У вас нет фильмов"),
+    ("list.empty.series", "У вас нет сериалов"),
+    ("list.empty.quote", "У вас нет цитат"),
+    ("list.empty_shelf", "На полке «{shelf}» нет книг"),
+    ("list.page", "{title} (страница {page}/{pages}):\n"),
+    ("list.more", "…и ещё {count}\n"),
+    ("list.delete", "Удалить {item}"),
+    ("list.previous", "« Назад"),
+    ("list.next", "Далее »"),
+    ("list.book.rated_on", "{title}, автор {author} (оценка: {rating}, прочитана {date})"),
+    ("list.book.rated", "{title}, автор {author} (оценка: {rating})"),
+    ("list.book.shelved", "{title}, автор {author} ({shelf})"),
+    ("list.movie.rated_on", "{movie} (оценка: {rating}, просмотрен {date})"),
+    ("list.movie.rated", "{movie} (оценка: {rating})"),
+    ("list.series", "{title} ({seasons}) (оценка: {rating})"),
+    ("list.quote.saved_on", "{quote} (сохранена {date})"),
+    ("watchlist.title", "Фильмы на потом"),
+    ("watchlist.empty", "Список фильмов на потом пуст"),
+    ("watchlist.empty_hint", "Список фильмов на потом пуст, добавьте фильм командой /watchlist <название> (<год>)"),
+    ("watchlist.added", "{item} добавлен в список на потом"),
+    ("watchlist.already", "{item} уже есть в списке на потом"),
+    ("watchlist.title_required", "Нужно указать название"),
+    ("watchlist.watched", "Посмотрел {item}"),
+    ("watchlist.remove", "Убрать"),
+    ("watchlist.removed", "Убрано"),
+    ("duplicate.question", "{item} уже есть в вашей библиотеке. Оставить обе записи, заменить старую или отбросить новую?"),
+    ("duplicate.keep", "Оставить обе"),
+    ("duplicate.replace", "Заменить"),
+    ("duplicate.discard", "Отбросить"),
+    ("duplicate.kept", "Оставлены обе записи: {item}"),
+    ("duplicate.replaced", "{old} заменено на {new}"),
+    ("duplicate.gone", "Старой записи больше нет, добавлено: {item}"),
+    ("duplicate.discarded", "Новая запись отброшена: {item}"),
+    ("duplicate.answered", "На этот вопрос уже ответили"),
+    ("duplicate.unanswered", "На вопрос о записи {item} не ответили, оставлены обе записи"),
+    ("scale.choose", "Сейчас оценки показываются {scale}. Выберите шкалу оценок:"),
+    ("scale.changed", "Теперь оценки показываются {scale}"),
+    ("language.choose", "Сейчас выбран язык: {language}. Выберите язык:"),
+    ("language.changed", "Теперь сообщения на русском"),
+    ("search.usage", "Использование: /search <запрос>"),
+    ("search.nothing", "По запросу «{query}» ничего не найдено"),
+    ("search.results", "Результаты поиска по запросу «{query}»:\n{results}"),
+    ("search.header.book", "Книги"),
+    ("search.header.movie", "Фильмы"),
+    ("search.header.series", "Сериалы"),
+    ("search.header.quote", "Цитаты"),
+    ("stats.empty", "Ваша библиотека пуста, добавьте что-нибудь командами /add_book, /add_movie, /add_series или /add_quote"),
+    ("stats.library", "Ваша библиотека: {books}, {movies}, {series}, {quotes}\n"),
+    ("stats.shelves", "Книги: {shelves}\n"),
+    ("stats.by_year", "\nПо годам:\n"),
+    ("stats.no_date", "Без даты"),
+    ("stats.average", "\n{ratings}: в среднем {average}\n"),
+    ("stats.book_ratings", "Оценки книг"),
+    ("stats.movie_ratings", "Оценки фильмов"),
+    ("stats.series_ratings", "Оценки сериалов"),
+    ("stats.authors", "\nСамые читаемые авторы:\n"),
+    ("stats.sources", "\nСамые цитируемые источники:\n"),
+    ("export.choose", "Выберите формат экспорта:"),
+    ("export.caption", "Ваша библиотека"),
+    ("export.failed", "Экспорт не удался, попробуйте позже"),
+    ("config.title", "Конфигурация:"),
+    ("import.not_csv", "Отправьте CSV-экспорт из Goodreads или Letterboxd, чтобы импортировать библиотеку"),
+    ("import.too_large", "Файл слишком большой, импортировать можно файлы до {size} МБ"),
+    ("import.download_failed", "Не удалось скачать файл, попробуйте позже"),
+    ("import.unknown", "Это не похоже на экспорт из Goodreads или Letterboxd"),
+    ("import.summary", "Импортировано из {source}: {items}. Пропущено строк без оценки: {skipped}, повторов: {duplicates}."),
+    ("import.errors", "\n\nНе удалось разобрать строк: {count}\n"),
+    ("import.line", "строка {line}: {error}"),
+    ("import.line_title", "строка {line} ({title}): {error}"),
+    ("import.title_missing", "нет названия"),
+    ("import.title_author_required", "«{title}»: нужны название и автор"),
+];
@@ -1,17 +1,196 @@
-pub trait HasDialogMessage {
-    fn message(&self) -> Option<String>;
+// Declared first, so the `t!` macro is in scope in the other modules
+#[macro_use]
+mod i18n;
+mod form;
+mod rating;
+mod widgets;
+
+pub use form::*;
+pub use i18n::Language;
+pub use rating::*;
+pub use widgets::Widget;
+
+pub const KEEP_COMMAND: &str = "/keep";
+pub const BACK_COMMAND: &str = "/back";
+pub const SKIP_COMMAND: &str = "/skip";
+
+/// Errors of the validators are message keys, translated by the caller.
+pub fn validate_year(text: &String) -> Result<u32, &str> {
+    let year = text.parse::<u32>();
+    match year {
+        Ok(year) => {
+            if year >= 1900 && year <= 2100 {
+                Ok(year)
+            } else {
+                Err("error.year_range")
+            }
+        }
+        Err(_) => Err("error.year_number")
+    }
+}
+
+/// When editing an existing entry or going back to an answered step, `/keep` leaves the current value unchanged.
+pub fn kept_value<T: Clone>(text: &String, current: Option<&T>) -> Option<T> {
+    if text.trim() == KEEP_COMMAND {
+        current.cloned()
+    } else {
+        None
+    }
+}
+
+/// Appends the current value of the field to the step prompt of an edit dialog or a step returned to with `/back`.
+pub fn with_current_value(language: Language, message: String, current: Option<String>) -> String {
+    match current {
+        Some(current) => t!(language, "form.current_value", prompt = message, value = current, command = KEEP_COMMAND),
+        None => message,
+    }
+}
+
+/// Converts days since 1970-01-01 to (year, month, day) in the proleptic Gregorian calendar.
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Converts (year, month, day) to days since 1970-01-01, the inverse of `civil_from_days`.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let mp = (if month > 2 { month - 3 } else { month + 9 }) as i64;
+    let day_of_year = (153 * mp + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        _ => if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 { 29 } else { 28 },
+    }
+}
+
+/// Today's date in UTC, offset by `days_ago`.
+fn date_days_ago(days_ago: i64) -> String {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    let (year, month, day) = civil_from_days(seconds / 86400 - days_ago);
+    format!("{:04}-{:02}-{:02}", year, month, day)
 }
 
-pub fn validate_rating(text: &String) -> Result<u32, &str> {
-    let rating = text.parse::<u32>();
-    match rating {
-        Ok(rating) => {
-            if rating >= 1 && rating <= 5 {
-                Ok(rating)
+pub fn today() -> String {
+    date_days_ago(0)
+}
+
+/// Accepts `today`, `yesterday`, `YYYY-MM-DD`, `YYYY/MM/DD` or `DD.MM.YYYY` and returns `YYYY-MM-DD`.
+/// The words are also understood in Russian. `03/04/2024` is March 4 in the US and April 3 elsewhere,
+/// so day-first dates are only accepted with dots. Dates are of things already read, watched or saved,
+/// future dates are rejected.
+pub fn validate_date(text: &String) -> Result<String, &str> {
+    let text = text.trim().to_lowercase();
+    match text.as_str() {
+        "today" | "сегодня" => return Ok(date_days_ago(0)),
+        "yesterday" | "вчера" => return Ok(date_days_ago(1)),
+        _ => (),
+    }
+    let year_first: Vec<&str> = text.split(|c| c == '-' || c == '/').collect();
+    let day_first: Vec<&str> = text.split('.').collect();
+    let (year, month, day) = match (year_first.as_slice(), day_first.as_slice()) {
+        ([year, month, day], _) if year.len() == 4 => (*year, *month, *day),
+        (_, [day, month, year]) if year.len() == 4 => (*year, *month, *day),
+        ([_, _, year], _) if year.len() == 4 => return Err("error.date_ambiguous"),
+        _ => return Err("error.date_format"),
+    };
+    match (year.parse::<i64>(), month.parse::<u32>(), day.parse::<u32>()) {
+        (Ok(year), Ok(month), Ok(day)) => {
+            let date = format!("{:04}-{:02}-{:02}", year, month, day);
+            if year < 1900 || year > 2100 {
+                Err("error.year_range")
+            } else if month < 1 || month > 12 || day < 1 || day > days_in_month(year, month) {
+                Err("error.no_such_date")
+            } else if date > date_days_ago(-1) {
+                // Today is in UTC, a day ahead of it is still today east of Greenwich
+                Err("error.future_date")
             } else {
-                Err("Rating must be between 1 and 5")
+                Ok(date)
             }
         }
-        Err(_) => Err("Rating must be a number")
+        _ => Err("error.date_format"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> Result<String, String> {
+        validate_date(&text.to_string()).map_err(|key| key.to_string())
+    }
+
+    #[test]
+    fn accepts_year_first_and_dotted_day_first_dates() {
+        assert_eq!(date("2024-03-04"), Ok("2024-03-04".to_string()));
+        assert_eq!(date("2024/3/4"), Ok("2024-03-04".to_string()));
+        assert_eq!(date(" 04.03.2024 "), Ok("2024-03-04".to_string()));
+    }
+
+    #[test]
+    fn accepts_words_in_both_languages() {
+        assert_eq!(date("Today"), Ok(today()));
+        assert_eq!(date("сегодня"), Ok(today()));
+        assert_eq!(date("yesterday"), Ok(date_days_ago(1)));
+        assert_eq!(date("вчера"), Ok(date_days_ago(1)));
+    }
+
+    #[test]
+    fn rejects_ambiguous_day_first_dates() {
+        assert_eq!(date("03/04/2024"), Err("error.date_ambiguous".to_string()));
+        assert_eq!(date("03-04-2024"), Err("error.date_ambiguous".to_string()));
+    }
+
+    #[test]
+    fn rejects_malformed_dates() {
+        assert_eq!(date("2024-03"), Err("error.date_format".to_string()));
+        assert_eq!(date("2024.03.04"), Err("error.date_format".to_string()));
+        assert_eq!(date("2024-03.04"), Err("error.date_format".to_string()));
+        assert_eq!(date("2024-March-04"), Err("error.date_format".to_string()));
+        assert_eq!(date("last week"), Err("error.date_format".to_string()));
+    }
+
+    #[test]
+    fn rejects_dates_that_do_not_exist() {
+        assert_eq!(date("2023-02-29"), Err("error.no_such_date".to_string()));
+        assert_eq!(date("2024-02-29"), Ok("2024-02-29".to_string()));
+        assert_eq!(date("2024-13-01"), Err("error.no_such_date".to_string()));
+        assert_eq!(date("31.04.2024"), Err("error.no_such_date".to_string()));
+        assert_eq!(date("1899-12-31"), Err("error.year_range".to_string()));
+    }
+
+    #[test]
+    fn rejects_future_dates() {
+        assert_eq!(date("2099-01-01"), Err("error.future_date".to_string()));
+        assert_eq!(date(&date_days_ago(-2)), Err("error.future_date".to_string()));
+        assert_eq!(date(&date_days_ago(-1)), Ok(date_days_ago(-1)));
+    }
+
+    #[test]
+    fn converts_between_days_and_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        for days in [-1, 0, 59, 11016, 11017, 19782, 47482] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
     }
 }
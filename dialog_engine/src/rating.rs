@@ -0,0 +1,174 @@
+use crate::Language;
+
+use serde::{Deserialize, Serialize};
+
+/// Ratings are stored as a score out of `MAX_SCORE`, whatever scale they were entered on.
+/// Every scale step is a whole number of points: a star is 20, a half star or a point out of ten is 10.
+pub const MAX_SCORE: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RatingScale {
+    #[default]
+    FiveStars,
+    HalfStars,
+    TenPoints,
+}
+
+impl RatingScale {
+    pub const ALL: [RatingScale; 3] = [RatingScale::FiveStars, RatingScale::HalfStars, RatingScale::TenPoints];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            RatingScale::FiveStars => "5",
+            RatingScale::HalfStars => "half",
+            RatingScale::TenPoints => "10",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<RatingScale> {
+        RatingScale::ALL.into_iter().find(|scale| scale.code() == code)
+    }
+
+    pub fn name(&self, language: Language) -> String {
+        t!(language, &format!("rating.name.{}", self.code()))
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            RatingScale::FiveStars => "1-5",
+            RatingScale::HalfStars => "0.5-5",
+            RatingScale::TenPoints => "1-10",
+        }
+    }
+
+    fn step(&self) -> u32 {
+        match self {
+            RatingScale::FiveStars => 20,
+            RatingScale::HalfStars | RatingScale::TenPoints => 10,
+        }
+    }
+
+    /// Scores of every rating on the scale, lowest first.
+    pub fn scores(&self) -> Vec<u32> {
+        (1..=MAX_SCORE / self.step()).map(|rating| rating * self.step()).collect()
+    }
+
+    /// Rounds a score entered on another scale to the nearest rating on this one.
+    pub fn round(&self, score: u32) -> u32 {
+        ((score + self.step() / 2) / self.step()).max(1) * self.step()
+    }
+
+    /// Parses a rating on this scale and returns its score.
+    pub fn parse(&self, language: Language, text: &str) -> Result<u32, String> {
+        let error = t!(language, &format!("rating.range.{}", self.code()));
+        match self {
+            RatingScale::HalfStars => {
+                let stars = text.trim().replace(',', ".").parse::<f64>()
+                    .map_err(|_| t!(language, "rating.number"))?;
+                let half_stars = stars * 2.0;
+                if half_stars.fract() != 0.0 || half_stars < 1.0 || half_stars > 10.0 {
+                    Err(error)
+                } else {
+                    Ok(half_stars as u32 * self.step())
+                }
+            }
+            _ => {
+                let rating = text.trim().parse::<u32>()
+                    .map_err(|_| t!(language, "rating.number"))?;
+                if rating >= 1 && rating <= MAX_SCORE / self.step() {
+                    Ok(rating * self.step())
+                } else {
+                    Err(error)
+                }
+            }
+        }
+    }
+
+    pub fn format(&self, score: u32) -> String {
+        let score = self.round(score);
+        match self {
+            RatingScale::FiveStars => format!("{}", score / 20),
+            RatingScale::HalfStars if score % 20 == 0 => format!("{}", score / 20),
+            RatingScale::HalfStars => format!("{}.5", score / 20),
+            RatingScale::TenPoints => format!("{}", score / 10),
+        }
+    }
+
+    pub fn format_average(&self, score: f64) -> String {
+        let max = match self {
+            RatingScale::FiveStars | RatingScale::HalfStars => 5.0,
+            RatingScale::TenPoints => 10.0,
+        };
+        format!("{:.1}", score / MAX_SCORE as f64 * max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(scale: RatingScale, text: &str) -> Result<u32, String> {
+        scale.parse(Language::English, text)
+    }
+
+    #[test]
+    fn parses_ratings_into_scores() {
+        assert_eq!(parse(RatingScale::FiveStars, " 4 "), Ok(80));
+        assert_eq!(parse(RatingScale::HalfStars, "3.5"), Ok(70));
+        assert_eq!(parse(RatingScale::HalfStars, "3,5"), Ok(70));
+        assert_eq!(parse(RatingScale::HalfStars, "0.5"), Ok(10));
+        assert_eq!(parse(RatingScale::TenPoints, "7"), Ok(70));
+        assert_eq!(parse(RatingScale::TenPoints, "10"), Ok(100));
+    }
+
+    #[test]
+    fn rejects_ratings_off_the_scale() {
+        let range = |scale: RatingScale| Err(t!(Language::English, &format!("rating.range.{}", scale.code())));
+        assert_eq!(parse(RatingScale::FiveStars, "0"), range(RatingScale::FiveStars));
+        assert_eq!(parse(RatingScale::FiveStars, "6"), range(RatingScale::FiveStars));
+        assert_eq!(parse(RatingScale::HalfStars, "3.3"), range(RatingScale::HalfStars));
+        assert_eq!(parse(RatingScale::HalfStars, "5.5"), range(RatingScale::HalfStars));
+        assert_eq!(parse(RatingScale::TenPoints, "11"), range(RatingScale::TenPoints));
+
+        let number = Err(t!(Language::English, "rating.number"));
+        assert_eq!(parse(RatingScale::FiveStars, "four"), number);
+        assert_eq!(parse(RatingScale::FiveStars, "3.5"), number);
+        assert_eq!(parse(RatingScale::TenPoints, ""), number);
+    }
+
+    #[test]
+    fn formats_scores_on_the_scale() {
+        assert_eq!(RatingScale::FiveStars.format(80), "4");
+        assert_eq!(RatingScale::HalfStars.format(80), "4");
+        assert_eq!(RatingScale::HalfStars.format(70), "3.5");
+        assert_eq!(RatingScale::TenPoints.format(70), "7");
+    }
+
+    #[test]
+    fn rounds_scores_from_finer_scales() {
+        assert_eq!(RatingScale::FiveStars.round(70), 80);
+        assert_eq!(RatingScale::FiveStars.round(50), 60);
+        assert_eq!(RatingScale::FiveStars.round(10), 20);
+        assert_eq!(RatingScale::FiveStars.format(70), "4");
+        assert_eq!(RatingScale::HalfStars.round(35), 40);
+        assert_eq!(RatingScale::TenPoints.round(100), 100);
+    }
+
+    #[test]
+    fn never_rounds_to_zero() {
+        for scale in RatingScale::ALL {
+            assert_eq!(scale.round(0), scale.scores()[0]);
+        }
+    }
+
+    #[test]
+    fn every_score_round_trips() {
+        for scale in RatingScale::ALL {
+            for score in scale.scores() {
+                assert_eq!(scale.round(score), score);
+                assert_eq!(parse(scale, &scale.format(score)), Ok(score));
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{Language, RatingScale, days_from_civil, days_in_month, today};
+
+use frankenstein::InlineKeyboardButton;
+
+const FIRST_YEAR: i64 = 1900;
+const LAST_YEAR: i64 = 2100;
+
+/// An inline keyboard shown under a field prompt. Typed answers are still accepted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Widget {
+    // Every rating on the scale
+    Rating(RatingScale),
+    // Decades first, then the years of the chosen decade
+    Year,
+    // The days of a month, with buttons to the previous and next months
+    Calendar,
+}
+
+/// What a widget button does, encoded in its `callback_data`.
+/// Actions carry the index of the field they were shown for, so buttons of an earlier step are ignored: `pick:2:4`.
+#[derive(Debug, PartialEq)]
+pub(crate) enum WidgetAction {
+    // Answers the field as if the text was typed
+    Pick(usize, String),
+    // Shows another view of the widget: `decades`, a decade (`1990`) or a month (`2024-03`)
+    Navigate(usize, String),
+    Skip(usize),
+    // Labels such as weekday names
+    Ignore,
+}
+
+impl WidgetAction {
+    pub(crate) fn parse(data: &str) -> Option<WidgetAction> {
+        let parts: Vec<&str> = data.splitn(3, ':').collect();
+        match parts.as_slice() {
+            ["pick", field, text] => Some(WidgetAction::Pick(field.parse().ok()?, text.to_string())),
+            ["nav", field, view] => Some(WidgetAction::Navigate(field.parse().ok()?, view.to_string())),
+            ["skip", field] => Some(WidgetAction::Skip(field.parse().ok()?)),
+            ["ignore"] => Some(WidgetAction::Ignore),
+            _ => None,
+        }
+    }
+
+    fn data(&self) -> String {
+        match self {
+            WidgetAction::Pick(field, text) => format!("pick:{}:{}", field, text),
+            WidgetAction::Navigate(field, view) => format!("nav:{}:{}", field, view),
+            WidgetAction::Skip(field) => format!("skip:{}", field),
+            WidgetAction::Ignore => "ignore".to_string(),
+        }
+    }
+}
+
+fn button(text: &str, action: WidgetAction) -> InlineKeyboardButton {
+    InlineKeyboardButton::builder()
+        .text(text)
+        .callback_data(action.data())
+        .build()
+}
+
+fn label(text: &str) -> InlineKeyboardButton {
+    button(text, WidgetAction::Ignore)
+}
+
+pub(crate) fn skip_row(field: usize, language: Language) -> Vec<InlineKeyboardButton> {
+    vec![button(&t!(language, "widget.skip"), WidgetAction::Skip(field))]
+}
+
+/// (Year, Month) of today.
+fn current_month() -> (i64, u32) {
+    let today = today();
+    let year = today[..4].parse().unwrap_or(FIRST_YEAR);
+    let month = today[5..7].parse().unwrap_or(1);
+    (year, month)
+}
+
+impl Widget {
+    /// Rows of the view shown with the prompt.
+    pub(crate) fn keyboard(&self, field: usize, language: Language) -> Vec<Vec<InlineKeyboardButton>> {
+        match self {
+            Widget::Rating(scale) => {
+                let buttons: Vec<InlineKeyboardButton> = scale.scores().into_iter()
+                    .map(|score| {
+                        let rating = scale.format(score);
+                        let text = match scale {
+                            RatingScale::TenPoints => rating.clone(),
+                            _ => format!("{}★", rating),
+                        };
+                        button(&text, WidgetAction::Pick(field, rating))
+                    })
+                    .collect();
+                buttons.chunks(5).map(|row| row.to_vec()).collect()
+            }
+            Widget::Year => decades(field, language),
+            Widget::Calendar => {
+                let (year, month) = current_month();
+                calendar(field, year, month, language)
+            }
+        }
+    }
+
+    /// Rows of a view navigated to, `None` if the view doesn't exist.
+    pub(crate) fn view(&self, field: usize, view: &str, language: Language) -> Option<Vec<Vec<InlineKeyboardButton>>> {
+        match self {
+            Widget::Rating(_) => None,
+            Widget::Year if view == "decades" => Some(decades(field, language)),
+            Widget::Year => view.parse::<i64>().ok().map(|decade| years(field, decade, language)),
+            Widget::Calendar => {
+                let (year, month) = view.split_once('-')?;
+                let (year, month) = (year.parse::<i64>().ok()?, month.parse::<u32>().ok()?);
+                if (FIRST_YEAR..=LAST_YEAR).contains(&year) && (1..=12).contains(&month) {
+                    Some(calendar(field, year, month, language))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn decades(field: usize, language: Language) -> Vec<Vec<InlineKeyboardButton>> {
+    let (year, _) = current_month();
+    let buttons: Vec<InlineKeyboardButton> = (FIRST_YEAR..=year / 10 * 10)
+        .step_by(10)
+        .map(|decade| button(&t!(language, "widget.decade", decade = decade), WidgetAction::Navigate(field, decade.to_string())))
+        .collect();
+    buttons.chunks(4).map(|row| row.to_vec()).collect()
+}
+
+fn years(field: usize, decade: i64, language: Language) -> Vec<Vec<InlineKeyboardButton>> {
+    let buttons: Vec<InlineKeyboardButton> = (decade..decade + 10)
+        .filter(|year| (FIRST_YEAR..=LAST_YEAR).contains(year))
+        .map(|year| button(&year.to_string(), WidgetAction::Pick(field, year.to_string())))
+        .collect();
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = buttons.chunks(5).map(|row| row.to_vec()).collect();
+    rows.push(vec![button(&t!(language, "widget.decades"), WidgetAction::Navigate(field, "decades".to_string()))]);
+    rows
+}
+
+fn calendar(field: usize, year: i64, month: u32, language: Language) -> Vec<Vec<InlineKeyboardButton>> {
+    let (previous_year, previous_month) = if month == 1 { (year - 1, 12) } else { (year, month - 1) };
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    let navigate = |year: i64, month: u32, text: &str| {
+        if (FIRST_YEAR..=LAST_YEAR).contains(&year) {
+            button(text, WidgetAction::Navigate(field, format!("{:04}-{:02}", year, month)))
+        } else {
+            label(" ")
+        }
+    };
+    let mut rows = vec![
+        vec![
+            navigate(previous_year, previous_month, "«"),
+            label(&t!(language, "widget.month", month = t!(language, &format!("month.{}", month)), year = year)),
+            navigate(next_year, next_month, "»"),
+        ],
+        (1..=7).map(|weekday| label(&t!(language, &format!("weekday.{}", weekday)))).collect(),
+    ];
+
+    // 1970-01-01 was a Thursday, weeks start on Monday
+    let first_weekday = (days_from_civil(year, month, 1) + 3).rem_euclid(7) as usize;
+    let mut days: Vec<InlineKeyboardButton> = (0..first_weekday).map(|_| label(" ")).collect();
+    for day in 1..=days_in_month(year, month) {
+        let date = format!("{:04}-{:02}-{:02}", year, month, day);
+        days.push(button(&day.to_string(), WidgetAction::Pick(field, date)));
+    }
+    while days.len() % 7 != 0 {
+        days.push(label(" "));
+    }
+    rows.extend(days.chunks(7).map(|week| week.to_vec()));
+
+    rows.push(vec![
+        button(&t!(language, "widget.today"), WidgetAction::Pick(field, "today".to_string())),
+        button(&t!(language, "widget.yesterday"), WidgetAction::Pick(field, "yesterday".to_string())),
+    ]);
+    rows
+}
@@ -48,6 +48,7 @@ use frankenstein::api_params::GetMyNameParams;
 use frankenstein::api_params::GetMyShortDescriptionParams;
 use frankenstein::api_params::GetStickerSetParams;
 use frankenstein::api_params::GetUpdatesParams;
+use frankenstein::api_params::InputFile;
 use frankenstein::api_params::GetUserProfilePhotosParams;
 use frankenstein::api_params::HideGeneralForumTopicParams;
 use frankenstein::api_params::InputMedia;
@@ -57,6 +58,7 @@ use frankenstein::api_params::PinChatMessageParams;
 use frankenstein::api_params::PromoteChatMemberParams;
 use frankenstein::api_params::ReopenForumTopicParams;
 use frankenstein::api_params::ReopenGeneralForumTopicParams;
+use frankenstein::api_params::ReplyMarkup;
 use frankenstein::api_params::RestrictChatMemberParams;
 use frankenstein::api_params::RevokeChatInviteLinkParams;
 use frankenstein::api_params::SendAnimationParams;
@@ -117,6 +119,7 @@ use frankenstein::objects::ChatMember;
 use frankenstein::objects::File as FileObject;
 use frankenstein::objects::ForumTopic;
 use frankenstein::objects::GameHighScore;
+use frankenstein::objects::InlineKeyboardMarkup;
 use frankenstein::objects::InputSticker;
 use frankenstein::objects::MenuButton;
 use frankenstein::objects::MessageId;
@@ -129,16 +132,32 @@ use frankenstein::objects::UserProfilePhotos;
 use frankenstein::objects::WebhookInfo;
 use frankenstein::GetCustomEmojiStickersParams;
 use frankenstein::Sticker;
+use frankenstein::AllowedUpdate;
 use frankenstein::UnpinAllGeneralForumTopicMessagesParams;
 use std::path::PathBuf;
 
 use frankenstein::objects::{Message, ResponseParameters};
 use serde::{Deserialize, Serialize};
 
+use std::env;
 use std::time::Duration;
 
 use typed_builder::TypedBuilder;
 
+pub mod retry;
+
+use retry::{Retry, RetryPolicy};
+
+/// The HTTP client. Golem's fork of reqwest is blocking, natively the blocking client of reqwest has the same API.
+#[cfg(target_arch = "wasm32")]
+pub use reqwest as http;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod http {
+    pub use reqwest_native::blocking::{Client, ClientBuilder, Response};
+    pub use reqwest_native::{Error, StatusCode};
+}
+
 pub fn send_message(api: &Api, chat_id: i64, text: &str) { // todo move to a separate module
     let message_params = SendMessageParams::builder()
         .chat_id(chat_id)
@@ -150,7 +169,110 @@ pub fn send_message(api: &Api, chat_id: i64, text: &str) { // todo move to a sep
     };
 }
 
-const BASE_API_URL: &str = "https://api.telegram.org/bot";
+pub fn send_message_with_keyboard(api: &Api, chat_id: i64, text: &str, keyboard: InlineKeyboardMarkup) {
+    let message_params = SendMessageParams::builder()
+        .chat_id(chat_id)
+        .text(text)
+        .reply_markup(ReplyMarkup::InlineKeyboardMarkup(keyboard))
+        .build();
+    let result = api.send_message(&message_params);
+    if let Err(err) = result {
+        println!("Error sending message: {:?}", err);
+    };
+}
+
+/// Uploads in-memory `content` as a document named `file_name`.
+pub fn send_document(api: &Api, chat_id: i64, file_name: &str, content: Vec<u8>, caption: &str) {
+    // The path only names the upload, the content is sent from memory
+    let document = FileUpload::InputFile(InputFile { path: PathBuf::from(file_name) });
+    let params = SendDocumentParams::builder()
+        .chat_id(chat_id)
+        .document(document)
+        .caption(caption)
+        .build();
+    let files = vec![FormFile {
+        field: "document".to_string(),
+        file_name: file_name.to_string(),
+        content,
+    }];
+    let result: Result<MethodResponse<Message>, Error> = api.request_with_form_files("sendDocument", &params, files);
+    if let Err(err) = result {
+        println!("Error sending document: {:?}", err);
+    };
+}
+
+/// Downloads the content of a file sent to the bot.
+pub fn download_file(api: &Api, file_id: &str) -> Result<Vec<u8>, Error> {
+    let params = GetFileParams::builder().file_id(file_id).build();
+    let file = api.get_file(&params)?.result;
+    let file_path = file
+        .file_path
+        .ok_or_else(|| Error::Decode(format!("File {file_id} has no path")))?;
+
+    let url = api.file_url(&file_path);
+    // Downloading is a GET, which can be sent again whether or not the first one was answered
+    api.retry.run("Downloading a file", |err: &Error| err.retry(true), || {
+        let response = api.client.get(&url).send()?;
+        if response.status().as_u16() != 200 {
+            return Err(Error::Http(HttpError {
+                code: response.status().as_u16(),
+                message: format!("Failed to download file {file_id}"),
+            }));
+        }
+        let content = response.bytes()?;
+
+        Ok(content.to_vec())
+    })
+}
+
+/// Registers `url` to receive updates instead of `get_updates`.
+/// Telegram sends `secret_token` in the `X-Telegram-Bot-Api-Secret-Token` header of every webhook request.
+pub fn set_webhook(api: &Api, url: &str, secret_token: &str, allowed_updates: Vec<AllowedUpdate>) -> Result<(), Error> {
+    let params = SetWebhookParams::builder()
+        .url(url)
+        .secret_token(secret_token)
+        .allowed_updates(allowed_updates)
+        .build();
+    api.set_webhook(&params).map(|_| ())
+}
+
+pub fn edit_message_text(api: &Api, chat_id: i64, message_id: i32, text: &str, keyboard: Option<InlineKeyboardMarkup>) {
+    let params = match keyboard {
+        Some(keyboard) => EditMessageTextParams::builder()
+            .chat_id(chat_id)
+            .message_id(message_id)
+            .text(text)
+            .reply_markup(keyboard)
+            .build(),
+        None => EditMessageTextParams::builder()
+            .chat_id(chat_id)
+            .message_id(message_id)
+            .text(text)
+            .build(),
+    };
+    let result = api.edit_message_text(&params);
+    if let Err(err) = result {
+        println!("Error editing message: {:?}", err);
+    };
+}
+
+pub fn answer_callback_query(api: &Api, callback_query_id: &str, text: Option<&str>) {
+    let params = match text {
+        Some(text) => AnswerCallbackQueryParams::builder()
+            .callback_query_id(callback_query_id)
+            .text(text)
+            .build(),
+        None => AnswerCallbackQueryParams::builder()
+            .callback_query_id(callback_query_id)
+            .build(),
+    };
+    let result = api.answer_callback_query(&params);
+    if let Err(err) = result {
+        println!("Error answering callback query: {:?}", err);
+    };
+}
+
+const BASE_API_URL: &str = "https://api.telegram.org";
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, thiserror::Error)]
 #[serde(untagged)]
@@ -163,6 +285,36 @@ pub enum Error {
     Decode(String),
     #[error("Encode Error {0}")]
     Encode(String),
+    /// No response, e.g. the connection failed or timed out, so the request may or may not have been handled.
+    #[error("Network Error {0}")]
+    Network(String),
+}
+
+/// Methods that have the same effect when sent twice, so they can be sent again after getting no response.
+/// Others, like `sendMessage`, could be carried out twice.
+const IDEMPOTENT_METHODS: [&str; 4] = ["getUpdates", "getFile", "answerCallbackQuery", "setWebhook"];
+
+pub fn is_idempotent(method: &str) -> bool {
+    IDEMPOTENT_METHODS.contains(&method)
+}
+
+impl Error {
+    /// Whether the same request may succeed later: flood control and server errors,
+    /// and network errors if the request is `idempotent`.
+    pub fn retry(&self, idempotent: bool) -> Retry {
+        match self {
+            Error::Api(ErrorResponse { error_code: 429, parameters, .. }) => {
+                match parameters.as_ref().and_then(|parameters| parameters.retry_after) {
+                    Some(seconds) => Retry::After(Duration::from_secs(seconds as u64)),
+                    None => Retry::WithBackoff,
+                }
+            }
+            Error::Api(response) if response.error_code >= 500 => Retry::WithBackoff,
+            Error::Http(HttpError { code, .. }) if *code == 429 || *code >= 500 => Retry::WithBackoff,
+            Error::Network(_) if idempotent => Retry::WithBackoff,
+            _ => Retry::Never,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, thiserror::Error)]
@@ -172,25 +324,52 @@ pub struct HttpError {
     pub message: String,
 }
 
+/// A file part of a `multipart/form-data` request.
+#[derive(Debug, Clone)]
+pub struct FormFile {
+    pub field: String,
+    pub file_name: String,
+    pub content: Vec<u8>,
+}
+
 #[derive(Debug, Clone, TypedBuilder)]
 pub struct Api {
     #[builder(setter(into))]
     pub api_url: String,
     #[builder(
-        default_code = "reqwest::ClientBuilder::new().connect_timeout(Duration::from_secs(10)).timeout(Duration::from_secs(500)).build().unwrap()"
+        default_code = "http::ClientBuilder::new().connect_timeout(Duration::from_secs(10)).timeout(Duration::from_secs(500)).build().unwrap()"
     )]
-    pub client: reqwest::Client,
+    pub client: http::Client,
+    // Failed requests are sent again if `Error::retry` allows it for the method
+    #[builder(default)]
+    pub retry: RetryPolicy,
 }
 
 impl Api {
 
     /// Create a new `Api`. You can use `Api::builder()` for more options.
+    /// Requests go to `TELEGRAM_API_URL` if it is set, e.g. a local Bot API server or a stub for development.
     pub fn new(api_key: &str) -> Self {
-        let api_url = format!("{}{api_key}", BASE_API_URL);
+        let base_url = env::var("TELEGRAM_API_URL").unwrap_or_else(|_| BASE_API_URL.to_string());
+        Self::with_base_url(&base_url, api_key)
+    }
+
+    /// Create a new `Api` for the Bot API at `base_url`, e.g. `https://api.telegram.org`.
+    pub fn with_base_url(base_url: &str, api_key: &str) -> Self {
+        let api_url = format!("{}/bot{api_key}", base_url.trim_end_matches('/'));
 
         Self::builder().api_url(api_url).build()
     }
 
+    /// Files are served from `<base>/file/bot<token>/<file_path>`.
+    pub fn file_url(&self, file_path: &str) -> String {
+        // The token has no slashes, so the last `/bot` is the one before it, whatever the base URL
+        match self.api_url.rsplit_once("/bot") {
+            Some((base_url, api_key)) => format!("{base_url}/file/bot{api_key}/{file_path}"),
+            None => format!("{}/{file_path}", self.api_url),
+        }
+    }
+
     /// Create a new `Api`. You can use `Api::builder()` for more options.
     // pub fn new_url<T: Into<String>>(api_url: T) -> Self {
     //     Api::builder().api_url(api_url).build()
@@ -203,7 +382,7 @@ impl Api {
     }
 
     pub fn decode_response<T: serde::de::DeserializeOwned>(
-        response: reqwest::Response,
+        response: http::Response,
     ) -> Result<T, Error> {
         let status_code = response.status().as_u16();
         match response.text() {
@@ -213,8 +392,11 @@ impl Api {
                     return Ok(success_response);
                 }
 
-                let error_response: ErrorResponse = Self::parse_json(&message)?;
-                Err(Error::Api(error_response))
+                // A proxy in front of the Bot API may answer with something else, e.g. an HTML 502 page
+                match Self::parse_json::<ErrorResponse>(&message) {
+                    Ok(error_response) => Err(Error::Api(error_response)),
+                    Err(_) => Err(Error::Http(HttpError { code: status_code, message })),
+                }
             }
             Err(e) => {
                 let err = Error::Decode(format!("Failed to decode response: {e:?}"));
@@ -223,6 +405,74 @@ impl Api {
         }
     }
 
+    /// Sends `params` as form fields along with in-memory files. Fields named after a file are
+    /// skipped, non-string values are sent JSON-encoded as the Bot API expects.
+    pub fn request_with_form_files<
+        T1: serde::ser::Serialize + std::fmt::Debug,
+        T2: serde::de::DeserializeOwned,
+    >(
+        &self,
+        method: &str,
+        params: T1,
+        files: Vec<FormFile>,
+    ) -> Result<T2, Error> {
+        let url = format!("{}/{method}", self.api_url);
+        let boundary = format!("----MediaTrackerBoundary{:016x}", rand::random::<u64>());
+        let body = Self::encode_multipart(&params, &files, &boundary)?;
+
+        self.retry.run(method, |err: &Error| err.retry(is_idempotent(method)), || {
+            let response = self
+                .client
+                .post(&url)
+                .header("Content-Type", format!("multipart/form-data; boundary={boundary}"))
+                .body(body.clone())
+                .send()?;
+            Self::decode_response(response)
+        })
+    }
+
+    fn encode_multipart<T: serde::ser::Serialize + std::fmt::Debug>(
+        params: &T,
+        files: &[FormFile],
+        boundary: &str,
+    ) -> Result<Vec<u8>, Error> {
+        let json_value = serde_json::to_value(params)
+            .map_err(|e| Error::Encode(format!("{e:?} : {params:?}")))?;
+        let fields = json_value
+            .as_object()
+            .ok_or_else(|| Error::Encode(format!("Params are not an object: {params:?}")))?;
+
+        let mut body: Vec<u8> = vec![];
+        for (key, value) in fields {
+            if value.is_null() || files.iter().any(|file| &file.field == key) {
+                continue;
+            }
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            body.extend_from_slice(
+                format!("--{boundary}\r\nContent-Disposition: form-data; name=\"{key}\"\r\n\r\n{value}\r\n")
+                    .as_bytes(),
+            );
+        }
+        for file in files {
+            body.extend_from_slice(
+                format!(
+                    "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                    file.field,
+                    file.file_name.replace('"', "'")
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(&file.content);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        Ok(body)
+    }
+
     fn parse_json<T: serde::de::DeserializeOwned>(body: &str) -> Result<T, Error> {
         let json_result: Result<T, serde_json::Error> = serde_json::from_str(body);
 
@@ -237,15 +487,13 @@ impl Api {
     }
 }
 
-impl From<reqwest::Error> for Error {
-    fn from(error: reqwest::Error) -> Self {
+impl From<http::Error> for Error {
+    fn from(error: http::Error) -> Self {
         let message = error.to_string();
-        let code = error
-            .status()
-            .map_or(500, |status_code| status_code.as_u16());
-
-        let error = HttpError { code, message };
-        Self::Http(error)
+        match error.status() {
+            Some(status_code) => Self::Http(HttpError { code: status_code.as_u16(), message }),
+            None => Self::Network(message),
+        }
     }
 }
 
@@ -269,24 +517,24 @@ impl TelegramApi for Api {
         params: Option<T1>,
     ) -> Result<T2, Self::Error> {
         let url = format!("{}/{method}", self.api_url);
-
-        let mut prepared_request = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json");
-
-        prepared_request = if let Some(data) = params {
-            let json_string = Self::encode_params(&data)?;
-
-            prepared_request.body(json_string)
-        } else {
-            prepared_request
+        let json_string = match params {
+            Some(data) => Some(Self::encode_params(&data)?),
+            None => None,
         };
 
-        let response = prepared_request.send()?;
-        let parsed_response: T2 = Self::decode_response(response)?;
+        self.retry.run(method, |err: &Error| err.retry(is_idempotent(method)), || {
+            let mut prepared_request = self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json");
 
-        Ok(parsed_response)
+            if let Some(json_string) = &json_string {
+                prepared_request = prepared_request.body(json_string.clone());
+            }
+
+            let response = prepared_request.send()?;
+            Self::decode_response(response)
+        })
     }
 
     fn request_with_form_data<
@@ -294,11 +542,23 @@ impl TelegramApi for Api {
         T2: serde::de::DeserializeOwned,
     >(
         &self,
-        _method: &str,
-        _params: T1,
-        _files: Vec<(&str, PathBuf)>,
+        method: &str,
+        params: T1,
+        files: Vec<(&str, PathBuf)>,
     ) -> Result<T2, Error> {
-        unimplemented!("no multipart");
+        let mut form_files = vec![];
+        for (field, path) in files {
+            let file_name = path
+                .file_name()
+                .map_or_else(|| field.to_string(), |name| name.to_string_lossy().to_string());
+            form_files.push(FormFile {
+                field: field.to_string(),
+                file_name,
+                content: std::fs::read(&path)?,
+            });
+        }
+
+        self.request_with_form_files(method, params, form_files)
     }
 }
 
@@ -1452,3 +1712,49 @@ pub trait TelegramApi {
         params: Option<T1>,
     ) -> Result<T2, Self::Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(json: &str) -> Error {
+        Error::Api(serde_json::from_str(json).unwrap())
+    }
+
+    fn http_error(code: u16) -> Error {
+        Error::Http(HttpError { code, message: String::new() })
+    }
+
+    #[test]
+    fn flood_control_waits_as_long_as_told() {
+        let error = api_error(r#"{"ok":false,"error_code":429,"description":"Too Many Requests","parameters":{"retry_after":5}}"#);
+        assert_eq!(error.retry(false), Retry::After(Duration::from_secs(5)));
+        let error = api_error(r#"{"ok":false,"error_code":429,"description":"Too Many Requests"}"#);
+        assert_eq!(error.retry(false), Retry::WithBackoff);
+    }
+
+    #[test]
+    fn server_errors_are_retried_for_every_method() {
+        let error = api_error(r#"{"ok":false,"error_code":502,"description":"Bad Gateway"}"#);
+        assert_eq!(error.retry(false), Retry::WithBackoff);
+        assert_eq!(http_error(503).retry(false), Retry::WithBackoff);
+        assert_eq!(http_error(429).retry(false), Retry::WithBackoff);
+    }
+
+    #[test]
+    fn client_errors_are_never_retried() {
+        let error = api_error(r#"{"ok":false,"error_code":400,"description":"Bad Request: chat not found"}"#);
+        assert_eq!(error.retry(true), Retry::Never);
+        assert_eq!(http_error(404).retry(true), Retry::Never);
+        assert_eq!(Error::Decode("{".to_string()).retry(true), Retry::Never);
+    }
+
+    #[test]
+    fn requests_without_a_response_are_retried_only_if_idempotent() {
+        let error = Error::Network("operation timed out".to_string());
+        assert_eq!(error.retry(is_idempotent("getUpdates")), Retry::WithBackoff);
+        assert_eq!(error.retry(is_idempotent("answerCallbackQuery")), Retry::WithBackoff);
+        assert_eq!(error.retry(is_idempotent("sendMessage")), Retry::Never);
+        assert_eq!(error.retry(is_idempotent("editMessageText")), Retry::Never);
+    }
+}
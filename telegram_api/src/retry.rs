@@ -0,0 +1,161 @@
+use std::fmt::Debug;
+use std::thread;
+use std::time::Duration;
+
+/// What to do after a failed attempt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retry {
+    /// The call can't succeed as made, e.g. a bad request.
+    Never,
+    /// A transient failure: no response, flood control or a server error.
+    WithBackoff,
+    /// The server said how long to wait, e.g. `retry_after` of the Bot API on HTTP 429.
+    After(Duration),
+}
+
+/// Exponential backoff with jitter: the n-th retry waits a random time between half
+/// and all of `base_delay * 2^(n-1)`, capped at `max_delay`, so calls that failed together
+/// don't all retry at the same moment.
+/// The backoff waits block the caller, so they add up to at most `max_total_delay`.
+/// A wait the server asks for is always honored up to `max_retry_after`, retrying sooner would fail again.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    // Attempts in total, including the first one
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub max_total_delay: Duration,
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    // Requests are made while handling an update, which holds up the updates after it
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(2),
+            max_total_delay: Duration::from_secs(5),
+            // Flood control of the Bot API applies to every chat, so other updates couldn't be answered meanwhile anyway
+            max_retry_after: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self.base_delay.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)));
+        let half = exponential.min(self.max_delay) / 2;
+        half + half.mul_f64(rand::random::<f64>())
+    }
+
+    /// Makes the call until it succeeds, fails for good or runs out of attempts, `classify` tells which.
+    /// The error of the last attempt is returned.
+    pub fn run<T, E: Debug>(&self, name: &str, classify: impl Fn(&E) -> Retry, mut call: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        let mut attempt = 1;
+        let mut backed_off = Duration::ZERO;
+        loop {
+            let err = match call() {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            let delay = match classify(&err) {
+                Retry::Never => return Err(err),
+                _ if attempt >= self.max_attempts => return Err(err),
+                Retry::WithBackoff => {
+                    let delay = self.backoff(attempt);
+                    if backed_off + delay > self.max_total_delay {
+                        return Err(err);
+                    }
+                    backed_off += delay;
+                    delay
+                }
+                Retry::After(delay) if delay > self.max_retry_after => return Err(err),
+                Retry::After(delay) => delay,
+            };
+            println!("{} failed (attempt {} of {}), retrying in {:?}: {:?}", name, attempt, self.max_attempts, delay, err);
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+            max_total_delay: Duration::from_millis(10),
+            max_retry_after: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_with_jitter_up_to_the_max_delay() {
+        let policy = RetryPolicy::default();
+        for (retry, full) in [(1, 500), (2, 1000), (3, 2000), (4, 2000), (40, 2000)] {
+            let full = Duration::from_millis(full);
+            for _ in 0..100 {
+                let delay = policy.backoff(retry);
+                assert!(delay >= full / 2 && delay <= full, "retry {}: {:?}", retry, delay);
+            }
+        }
+    }
+
+    #[test]
+    fn default_waits_fit_in_the_total_delay() {
+        let policy = RetryPolicy::default();
+        let longest: Duration = (1..policy.max_attempts)
+            .map(|retry| (policy.base_delay * 2u32.pow(retry - 1)).min(policy.max_delay))
+            .sum();
+        assert!(longest <= policy.max_total_delay);
+    }
+
+    fn run(policy: RetryPolicy, retry: Retry, succeed_on: u32) -> (Result<u32, &'static str>, u32) {
+        let attempts = Cell::new(0);
+        let result = policy.run("Test", |_| retry, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() == succeed_on { Ok(attempts.get()) } else { Err("failed") }
+        });
+        (result, attempts.get())
+    }
+
+    #[test]
+    fn retries_until_the_call_succeeds() {
+        assert_eq!(run(policy(), Retry::WithBackoff, 3), (Ok(3), 3));
+    }
+
+    #[test]
+    fn stops_after_the_last_attempt() {
+        assert_eq!(run(policy(), Retry::WithBackoff, 0), (Err("failed"), 4));
+    }
+
+    #[test]
+    fn never_retries_what_cannot_succeed() {
+        assert_eq!(run(policy(), Retry::Never, 2), (Err("failed"), 1));
+    }
+
+    #[test]
+    fn backs_off_no_longer_than_the_total_delay() {
+        // Every backoff is at least 2 ms
+        let policy = RetryPolicy { base_delay: Duration::from_millis(4), max_total_delay: Duration::from_millis(1), ..policy() };
+        assert_eq!(run(policy, Retry::WithBackoff, 2), (Err("failed"), 1));
+    }
+
+    #[test]
+    fn waits_as_long_as_the_server_asks_beyond_the_total_delay() {
+        assert_eq!(run(policy(), Retry::After(Duration::from_millis(20)), 2), (Ok(2), 2));
+        assert_eq!(run(policy(), Retry::After(Duration::from_millis(20)), 0), (Err("failed"), 4));
+    }
+
+    #[test]
+    fn gives_up_if_the_server_asks_to_wait_too_long() {
+        assert_eq!(run(policy(), Retry::After(Duration::from_secs(30)), 2), (Err("failed"), 1));
+    }
+}